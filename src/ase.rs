@@ -0,0 +1,659 @@
+//! Reading Aseprite's own `.ase` files, so sprites don't need exporting by hand.
+//!
+//! Only the parts the game draws are read: frames (and how long each lasts), layers, cels, tags
+//! and the palette. Everything else in the file (user data, slices, color profiles...) is skipped.
+//! The format is documented at
+//! <https://github.com/aseprite/aseprite/blob/master/docs/ase-file-specs.md>.
+//!
+//! `AseFile::export` packs the frames into a grid and describes them the same way Aseprite's JSON
+//! export does, so an `.ase` and an exported `.json` end up as the same `SpriteSheetData`.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use flate2::read::ZlibDecoder;
+use serde_json;
+
+
+const FILE_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+const HEADER_SIZE: usize = 128;
+const FRAME_HEADER_SIZE: usize = 16;
+
+const CHUNK_OLD_PALETTE: u16 = 0x0004;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+
+#[derive(Debug)]
+pub enum AseError {
+    Io(io::Error),
+    /// Not an Aseprite file at all (or a frame that doesn't start where it should).
+    BadMagic,
+    /// The file ended in the middle of something.
+    Truncated,
+    /// Something we don't know how to read, like a color depth other than 8, 16 or 32.
+    Unsupported(String),
+}
+
+impl fmt::Display for AseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AseError::Io(ref e) => write!(f, "couldn't read .ase file: {}", e),
+            AseError::BadMagic => write!(f, "not an .ase file"),
+            AseError::Truncated => write!(f, ".ase file ends early"),
+            AseError::Unsupported(ref what) => write!(f, "unsupported .ase feature: {}", what),
+        }
+    }
+}
+
+impl Error for AseError {
+    fn description(&self) -> &str {
+        match *self {
+            AseError::Io(_) => "ase io error",
+            AseError::BadMagic => "bad ase magic number",
+            AseError::Truncated => "truncated ase file",
+            AseError::Unsupported(_) => "unsupported ase feature",
+        }
+    }
+}
+
+impl From<io::Error> for AseError {
+    fn from(e: io::Error) -> Self {
+        AseError::Io(e)
+    }
+}
+
+/// Little-endian reads over a slice, failing with `Truncated` instead of panicking.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data: data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], AseError> {
+        if self.data.len() - self.pos < len {
+            return Err(AseError::Truncated);
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), AseError> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, AseError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, AseError> {
+        let b = self.bytes(2)?;
+        Ok(b[0] as u16 | (b[1] as u16) << 8)
+    }
+
+    fn i16(&mut self) -> Result<i16, AseError> {
+        self.u16().map(|n| n as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, AseError> {
+        let b = self.bytes(4)?;
+        Ok(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24)
+    }
+
+    fn string(&mut self) -> Result<String, AseError> {
+        let len = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    Difference,
+    /// Anything else Aseprite can do. Drawn as `Normal`.
+    Other(u16),
+}
+
+impl BlendMode {
+    fn from_u16(mode: u16) -> Self {
+        match mode {
+            0 => BlendMode::Normal,
+            1 => BlendMode::Multiply,
+            2 => BlendMode::Screen,
+            3 => BlendMode::Overlay,
+            4 => BlendMode::Darken,
+            5 => BlendMode::Lighten,
+            10 => BlendMode::Difference,
+            other => BlendMode::Other(other),
+        }
+    }
+
+    /// Blend one channel of `src` over `dst`.
+    fn channel(&self, dst: u32, src: u32) -> u32 {
+        match *self {
+            BlendMode::Multiply => dst * src / 255,
+            BlendMode::Screen => dst + src - dst * src / 255,
+            BlendMode::Overlay => {
+                if dst < 128 { 2 * dst * src / 255 } else { 255 - 2 * (255 - dst) * (255 - src) / 255 }
+            },
+            BlendMode::Darken => dst.min(src),
+            BlendMode::Lighten => dst.max(src),
+            BlendMode::Difference => if dst > src { dst - src } else { src - dst },
+            BlendMode::Normal | BlendMode::Other(_) => src,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub name: String,
+    pub visible: bool,
+    /// Groups hold other layers rather than cels.
+    pub group: bool,
+    /// How deeply nested in groups the layer is (0 for the top level).
+    pub child_level: u16,
+    pub blend: BlendMode,
+    pub opacity: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+    PingPong,
+}
+
+impl Direction {
+    /// As written in Aseprite's JSON export.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Direction::Forward => "forward",
+            Direction::Reverse => "reverse",
+            Direction::PingPong => "pingpong",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    pub name: String,
+    /// First and last frame, inclusive.
+    pub from: usize,
+    pub to: usize,
+    pub direction: Direction,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CelContent {
+    /// `width * height` pixels in the file's color depth.
+    Pixels { width: usize, height: usize, data: Vec<u8> },
+    /// Same pixels as this layer's cel in another frame.
+    Linked(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cel {
+    pub layer: usize,
+    pub x: i32,
+    pub y: i32,
+    pub opacity: u8,
+    pub content: CelContent,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// In ms.
+    pub duration: u32,
+    pub cels: Vec<Cel>,
+}
+
+/// RGBA pixels, row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Image { width: width, height: height, rgba: vec![0; width * height * 4] }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * self.width + x) * 4;
+        [self.rgba[i], self.rgba[i + 1], self.rgba[i + 2], self.rgba[i + 3]]
+    }
+
+    /// Draw `src` `alpha` of the way over pixel `(x, y)`.
+    fn blend(&mut self, x: usize, y: usize, src: [u8; 4], alpha: u32, mode: BlendMode) {
+        let i = (y * self.width + x) * 4;
+        let (dr, dg, db, da) = (self.rgba[i] as u32, self.rgba[i + 1] as u32, self.rgba[i + 2] as u32,
+                                self.rgba[i + 3] as u32);
+        let (mut sr, mut sg, mut sb) = (src[0] as u32, src[1] as u32, src[2] as u32);
+        if da > 0 {
+            sr = mode.channel(dr, sr);
+            sg = mode.channel(dg, sg);
+            sb = mode.channel(db, sb);
+        }
+
+        let out_alpha = alpha + da * (255 - alpha) / 255;
+        if out_alpha == 0 {
+            return;
+        }
+        let mix = |dst: u32, src: u32| ((src * alpha + dst * da * (255 - alpha) / 255) / out_alpha) as u8;
+        self.rgba[i] = mix(dr, sr);
+        self.rgba[i + 1] = mix(dg, sg);
+        self.rgba[i + 2] = mix(db, sb);
+        self.rgba[i + 3] = out_alpha as u8;
+    }
+}
+
+/// Aseprite's JSON export, as much of it as `omn_labs` reads.
+#[derive(Serialize)]
+struct Export {
+    frames: Vec<ExportFrame>,
+    meta: ExportMeta,
+}
+
+#[derive(Serialize)]
+struct ExportRect {
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+}
+
+#[derive(Serialize)]
+struct ExportSize {
+    w: usize,
+    h: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportFrame {
+    filename: String,
+    frame: ExportRect,
+    rotated: bool,
+    trimmed: bool,
+    sprite_source_size: ExportRect,
+    source_size: ExportSize,
+    duration: u32,
+}
+
+#[derive(Serialize)]
+struct ExportTag {
+    name: String,
+    from: usize,
+    to: usize,
+    direction: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportMeta {
+    app: &'static str,
+    version: &'static str,
+    image: String,
+    format: &'static str,
+    size: ExportSize,
+    scale: &'static str,
+    frame_tags: Vec<ExportTag>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AseFile {
+    pub width: usize,
+    pub height: usize,
+    /// Bits per pixel: 32 (RGBA), 16 (grayscale) or 8 (indexed).
+    pub depth: u16,
+    pub frames: Vec<Frame>,
+    pub layers: Vec<Layer>,
+    pub tags: Vec<Tag>,
+    pub palette: Vec<[u8; 4]>,
+    /// The palette index that's see-through, for indexed sprites.
+    pub transparent_index: u8,
+}
+
+impl AseFile {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AseError> {
+        let mut bytes = vec![];
+        File::open(path)?.read_to_end(&mut bytes)?;
+        Self::parse(&bytes)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, AseError> {
+        let mut header = Reader::new(bytes);
+        header.skip(4)?; // file size
+        if header.u16()? != FILE_MAGIC {
+            return Err(AseError::BadMagic);
+        }
+        let frame_count = header.u16()? as usize;
+        let width = header.u16()? as usize;
+        let height = header.u16()? as usize;
+        let depth = header.u16()?;
+        let flags = header.u32()?;
+        header.skip(2 + 4 + 4)?; // speed (deprecated), then two zeros
+        let transparent_index = header.u8()?;
+        header.skip(HEADER_SIZE - header.pos)?;
+
+        if depth != 32 && depth != 16 && depth != 8 {
+            return Err(AseError::Unsupported(format!("{} bit color", depth)));
+        }
+
+        let mut ase = AseFile {
+            width: width,
+            height: height,
+            depth: depth,
+            frames: vec![],
+            layers: vec![],
+            tags: vec![],
+            palette: vec![],
+            transparent_index: transparent_index,
+        };
+        // older files don't store layer opacity
+        let layer_opacity = flags & 1 != 0;
+
+        let mut pos = HEADER_SIZE;
+        for _ in 0..frame_count {
+            let mut frame_header = Reader::new(&bytes[pos..]);
+            let frame_size = frame_header.u32()? as usize;
+            if frame_header.u16()? != FRAME_MAGIC {
+                return Err(AseError::BadMagic);
+            }
+            let old_chunks = frame_header.u16()? as usize;
+            let duration = frame_header.u16()? as u32;
+            frame_header.skip(2)?;
+            let new_chunks = frame_header.u32()? as usize;
+            if frame_size < FRAME_HEADER_SIZE || bytes.len() - pos < frame_size {
+                return Err(AseError::Truncated);
+            }
+
+            let mut frame = Frame { duration: duration, cels: vec![] };
+            let mut chunks = Reader::new(&bytes[pos + FRAME_HEADER_SIZE..pos + frame_size]);
+            for _ in 0..(if new_chunks == 0 { old_chunks } else { new_chunks }) {
+                let chunk_size = chunks.u32()? as usize;
+                let kind = chunks.u16()?;
+                if chunk_size < 6 {
+                    return Err(AseError::Truncated);
+                }
+                let mut chunk = Reader::new(chunks.bytes(chunk_size - 6)?);
+                ase.read_chunk(kind, &mut chunk, &mut frame, layer_opacity)?;
+            }
+
+            ase.frames.push(frame);
+            pos += frame_size;
+        }
+        Ok(ase)
+    }
+
+    fn read_chunk(&mut self, kind: u16, chunk: &mut Reader, frame: &mut Frame, layer_opacity: bool)
+                  -> Result<(), AseError> {
+        match kind {
+            CHUNK_LAYER => {
+                let flags = chunk.u16()?;
+                let layer_type = chunk.u16()?;
+                let child_level = chunk.u16()?;
+                chunk.skip(4)?; // default width and height, unused
+                let blend = BlendMode::from_u16(chunk.u16()?);
+                let opacity = chunk.u8()?;
+                chunk.skip(3)?;
+                let name = chunk.string()?;
+                self.layers.push(Layer {
+                    name: name,
+                    visible: flags & 1 != 0,
+                    group: layer_type == 1,
+                    child_level: child_level,
+                    blend: blend,
+                    opacity: if layer_opacity { opacity } else { 255 },
+                });
+            },
+            CHUNK_CEL => {
+                let layer = chunk.u16()? as usize;
+                let x = chunk.i16()? as i32;
+                let y = chunk.i16()? as i32;
+                let opacity = chunk.u8()?;
+                let cel_type = chunk.u16()?;
+                chunk.skip(7)?;
+
+                let content = match cel_type {
+                    0 | 2 => {
+                        let width = chunk.u16()? as usize;
+                        let height = chunk.u16()? as usize;
+                        let rest = chunk.bytes(chunk.data.len() - chunk.pos)?;
+                        let data = if cel_type == 0 {
+                            rest.to_vec()
+                        } else {
+                            let mut data = vec![];
+                            ZlibDecoder::new(rest).read_to_end(&mut data)?;
+                            data
+                        };
+                        if data.len() < width * height * self.bytes_per_pixel() {
+                            return Err(AseError::Truncated);
+                        }
+                        CelContent::Pixels { width: width, height: height, data: data }
+                    },
+                    1 => CelContent::Linked(chunk.u16()? as usize),
+                    other => return Err(AseError::Unsupported(format!("cel type {}", other))),
+                };
+                frame.cels.push(Cel { layer: layer, x: x, y: y, opacity: opacity, content: content });
+            },
+            CHUNK_TAGS => {
+                let count = chunk.u16()?;
+                chunk.skip(8)?;
+                for _ in 0..count {
+                    let from = chunk.u16()? as usize;
+                    let to = chunk.u16()? as usize;
+                    let direction = match chunk.u8()? {
+                        1 => Direction::Reverse,
+                        2 => Direction::PingPong,
+                        _ => Direction::Forward,
+                    };
+                    chunk.skip(8 + 3 + 1)?; // reserved, then the tag's color
+                    let name = chunk.string()?;
+                    self.tags.push(Tag { name: name, from: from, to: to, direction: direction });
+                }
+            },
+            CHUNK_PALETTE => {
+                let size = chunk.u32()? as usize;
+                let first = chunk.u32()? as usize;
+                let last = chunk.u32()? as usize;
+                chunk.skip(8)?;
+                if self.palette.len() < size {
+                    self.palette.resize(size, [0, 0, 0, 0]);
+                }
+                for idx in first..last + 1 {
+                    let flags = chunk.u16()?;
+                    let color = [chunk.u8()?, chunk.u8()?, chunk.u8()?, chunk.u8()?];
+                    if flags & 1 != 0 {
+                        chunk.string()?;
+                    }
+                    if idx < self.palette.len() {
+                        self.palette[idx] = color;
+                    }
+                }
+            },
+            // only there for older versions of Aseprite, which don't write the new one
+            CHUNK_OLD_PALETTE if self.palette.is_empty() => {
+                let packets = chunk.u16()?;
+                let mut idx = 0;
+                for _ in 0..packets {
+                    idx += chunk.u8()? as usize;
+                    let count = match chunk.u8()? { 0 => 256, n => n as usize };
+                    for _ in 0..count {
+                        let color = [chunk.u8()?, chunk.u8()?, chunk.u8()?, 255];
+                        if self.palette.len() <= idx {
+                            self.palette.resize(idx + 1, [0, 0, 0, 0]);
+                        }
+                        self.palette[idx] = color;
+                        idx += 1;
+                    }
+                }
+            },
+            _ => ()
+        }
+        Ok(())
+    }
+
+    fn bytes_per_pixel(&self) -> usize {
+        self.depth as usize / 8
+    }
+
+    /// The color of pixel `idx` of some cel data, or `None` if it's see-through.
+    fn color(&self, data: &[u8], idx: usize) -> Option<[u8; 4]> {
+        let i = idx * self.bytes_per_pixel();
+        let color = match self.depth {
+            32 => [data[i], data[i + 1], data[i + 2], data[i + 3]],
+            16 => [data[i], data[i], data[i], data[i + 1]],
+            _ => {
+                if data[i] == self.transparent_index {
+                    return None;
+                }
+                match self.palette.get(data[i] as usize) {
+                    Some(color) => *color,
+                    None => return None,
+                }
+            }
+        };
+        if color[3] == 0 { None } else { Some(color) }
+    }
+
+    /// Whether each layer ends up on screen: it has to be visible, and so does every group it's in.
+    fn layers_shown(&self) -> Vec<bool> {
+        let mut groups_shown: Vec<bool> = vec![];
+        self.layers.iter().map(|layer| {
+            let level = layer.child_level as usize;
+            groups_shown.truncate(level);
+            let parent_shown = groups_shown.last().cloned().unwrap_or(true);
+            let shown = parent_shown && layer.visible;
+            if layer.group {
+                groups_shown.push(shown);
+            }
+            shown && !layer.group
+        }).collect()
+    }
+
+    /// The pixels of this layer's cel in frame `idx`, following links to other frames.
+    fn cel_pixels(&self, idx: usize, layer: usize) -> Option<(&Cel, usize, usize, &[u8])> {
+        let cel = match self.frames.get(idx).and_then(|frame| frame.cels.iter().find(|cel| cel.layer == layer)) {
+            Some(cel) => cel,
+            None => return None
+        };
+        match cel.content {
+            CelContent::Pixels { width, height, ref data } => Some((cel, width, height, data)),
+            CelContent::Linked(other) if other != idx => {
+                // the link's position and opacity are its own, only the pixels are shared
+                self.cel_pixels(other, layer).map(|(_, width, height, data)| (cel, width, height, data))
+            },
+            CelContent::Linked(_) => None,
+        }
+    }
+
+    /// Flatten every visible layer of frame `idx`.
+    pub fn render_frame(&self, idx: usize) -> Image {
+        let mut image = Image::new(self.width, self.height);
+        for (layer_idx, shown) in self.layers_shown().into_iter().enumerate() {
+            if !shown {
+                continue;
+            }
+            let layer = &self.layers[layer_idx];
+            let (cel, width, height, data) = match self.cel_pixels(idx, layer_idx) {
+                Some(found) => found,
+                None => continue
+            };
+
+            for cy in 0..height {
+                let y = cel.y + cy as i32;
+                if y < 0 || y >= self.height as i32 {
+                    continue;
+                }
+                for cx in 0..width {
+                    let x = cel.x + cx as i32;
+                    if x < 0 || x >= self.width as i32 {
+                        continue;
+                    }
+                    if let Some(color) = self.color(data, cy * width + cx) {
+                        let alpha = color[3] as u32 * cel.opacity as u32 / 255 * layer.opacity as u32 / 255;
+                        if alpha > 0 {
+                            image.blend(x as usize, y as usize, color, alpha, layer.blend);
+                        }
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    /// Where frame `idx` goes in the atlas, with `columns` frames to a row.
+    fn cell(&self, idx: usize, columns: usize) -> (usize, usize) {
+        ((idx % columns) * self.width, (idx / columns) * self.height)
+    }
+
+    /// Every frame, in rows of a (near) square grid so a long animation doesn't make for a texture
+    /// wider than the GPU can take, and the JSON Aseprite would have exported alongside them (with
+    /// `meta.image` set to `image_name`).
+    pub fn export(&self, image_name: &str) -> (Image, String) {
+        let count = self.frames.len();
+        let columns = (1..).find(|columns| columns * columns >= count).unwrap();
+        let rows = (count + columns - 1) / columns;
+        let mut atlas = Image::new(self.width * columns, self.height * rows);
+        let row = self.width * 4;
+        for idx in 0..count {
+            let frame = self.render_frame(idx);
+            let (x, top) = self.cell(idx, columns);
+            for y in 0..self.height {
+                let start = ((top + y) * atlas.width + x) * 4;
+                atlas.rgba[start..start + row].copy_from_slice(&frame.rgba[y * row..(y + 1) * row]);
+            }
+        }
+
+        let stem = Path::new(image_name).file_stem().and_then(|stem| stem.to_str()).unwrap_or(image_name);
+        let export = Export {
+            frames: self.frames.iter().enumerate().map(|(idx, frame)| ExportFrame {
+                filename: format!("{} {}.ase", stem, idx),
+                frame: {
+                    let (x, y) = self.cell(idx, columns);
+                    ExportRect { x: x, y: y, w: self.width, h: self.height }
+                },
+                rotated: false,
+                trimmed: false,
+                sprite_source_size: ExportRect { x: 0, y: 0, w: self.width, h: self.height },
+                source_size: ExportSize { w: self.width, h: self.height },
+                duration: frame.duration,
+            }).collect(),
+            meta: ExportMeta {
+                app: "http://www.aseprite.org/",
+                version: "1.2-beta9",
+                image: image_name.to_string(),
+                format: "RGBA8888",
+                size: ExportSize { w: atlas.width, h: atlas.height },
+                scale: "1",
+                frame_tags: self.tags.iter().map(|tag| ExportTag {
+                    name: tag.name.clone(),
+                    from: tag.from,
+                    to: tag.to,
+                    direction: tag.direction.name(),
+                }).collect(),
+            },
+        };
+        // nothing in there can fail to serialize
+        let json = serde_json::to_string_pretty(&export).unwrap();
+        (atlas, json)
+    }
+}
@@ -0,0 +1,135 @@
+//! Sounds, by way of a channel of `SoundEvent`s that works just like the `DrawCommand` one.
+//!
+//! Systems say *what* happened (the pitcher started winding up, the ball came off the bat) and
+//! whoever is on the other end decides what that sounds like. The binary plays them through ggez,
+//! while headless runs and tests hand them to `NullAudio`, which only writes down what it was
+//! asked to play.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use toml;
+
+use meter::SwingGrade;
+
+
+pub const AUDIO_SETTINGS_FILE: &'static str = "audio.toml";
+/// How much the volume goes up or down per press.
+pub const VOLUME_STEP: f32 = 0.1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SoundEvent {
+    /// The pitching machine winding up.
+    WindupHum,
+    PitchLaunch,
+    /// The crack of the bat, depending on how well the swing was timed.
+    BatContact(SwingGrade),
+    CrowdCheer,
+}
+
+impl SoundEvent {
+    /// The file (in `resources/`) this event plays.
+    pub fn file(&self) -> &'static str {
+        match *self {
+            SoundEvent::WindupHum => "/sounds/windup.wav",
+            SoundEvent::PitchLaunch => "/sounds/launch.wav",
+            SoundEvent::BatContact(SwingGrade::Perfect) => "/sounds/crack-perfect.wav",
+            SoundEvent::BatContact(SwingGrade::Good) => "/sounds/crack-good.wav",
+            SoundEvent::BatContact(SwingGrade::Weak) => "/sounds/crack-weak.wav",
+            SoundEvent::CrowdCheer => "/sounds/cheer.wav",
+        }
+    }
+}
+
+/// Every sound, for loading them all up front.
+pub const SOUND_EVENTS: [SoundEvent; 6] = [
+    SoundEvent::WindupHum,
+    SoundEvent::PitchLaunch,
+    SoundEvent::BatContact(SwingGrade::Perfect),
+    SoundEvent::BatContact(SwingGrade::Good),
+    SoundEvent::BatContact(SwingGrade::Weak),
+    SoundEvent::CrowdCheer,
+];
+
+pub trait AudioBackend {
+    fn play(&mut self, event: &SoundEvent);
+    /// Called whenever the settings change (and once at the start).
+    fn apply(&mut self, settings: &AudioSettings);
+}
+
+/// Plays nothing, but remembers what it would have played.
+#[derive(Clone, Debug, Default)]
+pub struct NullAudio {
+    pub played: Vec<SoundEvent>,
+}
+
+impl AudioBackend for NullAudio {
+    fn play(&mut self, event: &SoundEvent) {
+        self.played.push(event.clone());
+    }
+
+    fn apply(&mut self, _settings: &AudioSettings) {}
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// From 0 to 1.
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings { volume: 0.8, muted: false }
+    }
+}
+
+impl AudioSettings {
+    /// What the volume actually is, taking muting into account.
+    pub fn effective_volume(&self) -> f32 {
+        if self.muted { 0. } else { self.volume }
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+    }
+
+    /// Turn the volume up (or down, for a negative `by`), unmuting if need be.
+    pub fn adjust(&mut self, by: f32) {
+        self.volume = (self.volume + by).max(0.).min(1.);
+        self.muted = false;
+    }
+
+    /// Load the settings saved in `dir`, or the defaults if there aren't any (or they're broken).
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(AUDIO_SETTINGS_FILE);
+        let mut text = String::new();
+        match File::open(&path).and_then(|mut file| file.read_to_string(&mut text)) {
+            Ok(_) => (),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                println!("Couldn't read audio settings from {}: {}", path.display(), e);
+                return Self::default();
+            }
+        }
+
+        match toml::from_str::<AudioSettings>(&text) {
+            Ok(mut settings) => {
+                settings.volume = settings.volume.max(0.).min(1.);
+                settings
+            },
+            Err(e) => {
+                println!("Ignoring audio settings in {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        let text = toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        fs::create_dir_all(dir)?;
+        File::create(dir.join(AUDIO_SETTINGS_FILE))?.write_all(text.as_bytes())
+    }
+}
@@ -0,0 +1,85 @@
+//! Where the screen is looking in the world.
+//!
+//! The world is laid out in the same pixels as the screen when looking at the plate, so the plate
+//! view is the identity whatever size the window is: a world point is drawn where it is. Once the
+//! ball is hit, `CameraSys` eases the camera after it (and out, for long drives), then back to the
+//! plate for the next at-bat.
+//!
+//! `Render` sends positions in world space and a `DrawCommand::Camera` ahead of them. Whatever
+//! does the drawing runs those positions through the camera with `apply`.
+
+use ggez::graphics::Point;
+
+use components::Ball;
+use render::Transform;
+
+
+/// How quickly the camera catches up with where it wants to be (the fraction of the gap closed
+/// per ms, roughly).
+pub const FOLLOW_RATE: f32 = 0.004;
+/// How far out the camera pulls for a long drive.
+pub const LONG_DRIVE_ZOOM: f32 = 0.5;
+/// Balls coming off the bat at least this fast (in pixels per ms) get the camera pulled out.
+pub const LONG_DRIVE_VELOCITY: f32 = 0.75;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// The world point in the middle of the screen.
+    pub center: Point,
+    /// Screen pixels per world pixel. Less than 1 shows more of the world.
+    pub zoom: f32,
+    /// The size of the window it's drawing to, from `WindowConfig::size`.
+    pub view: (f32, f32),
+}
+
+impl Camera {
+    pub fn new(center: Point, zoom: f32, view: (f32, f32)) -> Self {
+        Camera { center: center, zoom: zoom, view: view }
+    }
+
+    /// Looking at the plate, in a window of size `view`.
+    pub fn plate(view: (f32, f32)) -> Self {
+        Camera::new(Point::new(view.0 / 2., view.1 / 2.), 1., view)
+    }
+
+    pub fn world_to_screen(&self, world: Point) -> Point {
+        Point::new((world.x - self.center.x) * self.zoom + self.view.0 / 2.,
+                   (world.y - self.center.y) * self.zoom + self.view.1 / 2.)
+    }
+
+    pub fn screen_to_world(&self, screen: Point) -> Point {
+        Point::new((screen.x - self.view.0 / 2.) / self.zoom + self.center.x,
+                   (screen.y - self.view.1 / 2.) / self.zoom + self.center.y)
+    }
+
+    /// Move a world-space `Transform` onto the screen.
+    pub fn apply(&self, xform: &Transform) -> Transform {
+        Transform {
+            pos: self.world_to_screen(xform.pos),
+            scale: Point::new(xform.scale.x * self.zoom, xform.scale.y * self.zoom),
+            ..xform.clone()
+        }
+    }
+
+    /// Close some of the gap to `target`, depending on how long it's been. Frame rate doesn't
+    /// change where the camera ends up, only how many steps it takes to get there.
+    pub fn ease_toward(&mut self, target: &Camera, delta_ms: f32) {
+        let t = 1. - (-FOLLOW_RATE * delta_ms).exp();
+        self.center.x += (target.center.x - self.center.x) * t;
+        self.center.y += (target.center.y - self.center.y) * t;
+        self.zoom += (target.zoom - self.zoom) * t;
+    }
+
+    /// Where the camera wants to be: on the ball once it's been hit (further out if it was hit
+    /// hard), otherwise at the plate. A ball that's left play has stopped moving, so the camera
+    /// stays where it went out until the umpire clears it away.
+    pub fn target(&self, ball: Option<&Ball>) -> Self {
+        match ball {
+            Some(ball) if ball.struck => {
+                let zoom = if ball.velocity >= LONG_DRIVE_VELOCITY { LONG_DRIVE_ZOOM } else { 1. };
+                Camera::new(ball.pos, zoom, self.view)
+            },
+            _ => Camera::plate(self.view),
+        }
+    }
+}
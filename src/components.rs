@@ -2,6 +2,12 @@ use ggez::graphics::{Point, Rect};
 use specs;
 use omn_labs::sprites::{AnimationClip, SpriteSheetData};
 
+use meter::SwingGrade;
+use pitches::PitchSpec;
+
+/// Width and height of the ball (in pixels).
+pub const BALL_SIZE: f32 = 16.;
+
 
 #[derive(Clone, Debug)]
 pub struct GameFlow {
@@ -16,6 +22,8 @@ impl specs::Component for GameFlow {
 pub struct Pitcher {
     pub action_ttl: f32,
     pub active_clip: Option<AnimationClip>,
+    /// What's coming, picked when the windup starts.
+    pub pitch: Option<PitchSpec>,
 }
 
 impl specs::Component for Pitcher {
@@ -25,6 +33,7 @@ impl specs::Component for Pitcher {
 #[derive(Clone, Debug)]
 pub struct PowerMeter {
     pub time: f32,
+    /// From 0 to 1 (see `meter::MeterModel`).
     pub power_level: f32,
     pub active_clip: Option<AnimationClip>,
     pub pointer_clip: AnimationClip
@@ -34,6 +43,16 @@ impl specs::Component for PowerMeter {
     type Storage = specs::HashMapStorage<PowerMeter>;
 }
 
+/// Keeps track of how long the call on the last pitch stays up before the next at-bat.
+#[derive(Clone, Debug)]
+pub struct Umpire {
+    pub call_ttl: f32,
+}
+
+impl specs::Component for Umpire {
+    type Storage = specs::HashMapStorage<Umpire>;
+}
+
 #[derive(Clone, Debug)]
 pub struct Batter;
 
@@ -44,7 +63,14 @@ impl specs::Component for Batter {
 #[derive(Clone, Debug)]
 pub struct Bat {
     pub swinging: bool,
-    pub bbox: Rect
+    pub bbox: Rect,
+    /// How hard the current swing is, from the power meter at the moment the button was released.
+    pub power: f32,
+    /// How well the current swing was timed, if there is one.
+    pub grade: Option<SwingGrade>,
+    /// How long (in ms) since the current swing started.
+    pub swing_ms: f32,
+    pub active_clip: Option<AnimationClip>,
 }
 
 impl specs::Component for Bat {
@@ -57,7 +83,37 @@ pub struct Ball {
     pub pos: Point,
     pub angle: f32,
     pub velocity: f32,
-    pub out_of_bounds: bool
+    pub out_of_bounds: bool,
+    /// How far the ball has spun (in radians), just for looks.
+    pub rotation: f32,
+    /// How fast the heading bends on the way to the plate (see `pitches::apply_break`).
+    pub spin: f32,
+    /// Set once the ball has come off the bat so we don't keep reflecting it every tick while
+    /// the boxes still overlap.
+    pub struck: bool
+}
+
+impl Ball {
+    pub fn new(pos: Point, angle: f32, velocity: f32) -> Self {
+        let mut ball = Self {
+            bbox: Rect::new(0., 0., BALL_SIZE, BALL_SIZE),
+            pos: pos,
+            angle: angle,
+            velocity: velocity,
+            out_of_bounds: false,
+            rotation: 0.,
+            spin: 0.,
+            struck: false,
+        };
+        ball.sync_bbox();
+        ball
+    }
+
+    /// Re-center the bbox on the current position.
+    pub fn sync_bbox(&mut self) {
+        self.bbox.x = self.pos.x - (self.bbox.w / 2.);
+        self.bbox.y = self.pos.y - (self.bbox.h / 2.);
+    }
 }
 
 impl specs::Component for Ball {
@@ -0,0 +1,292 @@
+//! Settings that used to be literals: the window, timing, and where things get drawn.
+//!
+//! Read from a TOML file at startup (`resources/config.toml` unless `--config` says otherwise).
+//! Every key is optional and falls back to its default, so a config file only needs the things
+//! that are being changed:
+//!
+//! ```toml
+//! difficulty = "hard"
+//!
+//! [timing]
+//! windup_min_ms = 2000.0
+//! ```
+//!
+//! The `GameConfig` is added to the world as a resource, and the systems read their timing and
+//! layout from there.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use toml;
+
+use meter::MeterModel;
+use pitches::Difficulty;
+use timestep::DEFAULT_TICK_RATE;
+
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub width: u32,
+    pub height: u32,
+    pub title: String,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            width: 1024,
+            height: 768,
+            title: "Home World Derby".to_string(),
+        }
+    }
+}
+
+/// The window size that screen positions (in `LayoutConfig` and the scenes) are given for.
+pub const LAYOUT_SIZE: (f32, f32) = (1024., 768.);
+
+impl WindowConfig {
+    /// Width and height in pixels, for the `Camera`.
+    pub fn size(&self) -> (f32, f32) {
+        (self.width as f32, self.height as f32)
+    }
+
+    /// Where a screen position given for a `LAYOUT_SIZE` window ends up in this one, so the meter
+    /// and the text keep their place on the screen whatever size it is.
+    pub fn place(&self, pos: (f32, f32)) -> (f32, f32) {
+        (pos.0 * self.width as f32 / LAYOUT_SIZE.0, pos.1 * self.height as f32 / LAYOUT_SIZE.1)
+    }
+}
+
+/// All times are in ms.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimingConfig {
+    /// Simulation ticks per second.
+    pub tick_rate: u32,
+    /// The pitcher winds up for at least this long...
+    pub windup_min_ms: f32,
+    /// ...plus up to this much more, at random.
+    pub windup_range_ms: f32,
+    /// How long the call on a pitch is shown before the next at-bat.
+    pub call_hold_ms: f32,
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        TimingConfig {
+            tick_rate: DEFAULT_TICK_RATE,
+            windup_min_ms: 3000.,
+            windup_range_ms: 2500.,
+            call_hold_ms: 5000.,
+        }
+    }
+}
+
+/// Who's on the mound.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Opponent {
+    /// The pitching machine.
+    Machine,
+    /// The little green pitcher from `pitcher.ase`.
+    Alien,
+}
+
+impl Default for Opponent {
+    fn default() -> Self {
+        Opponent::Machine
+    }
+}
+
+impl Opponent {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "machine" => Some(Opponent::Machine),
+            "alien" => Some(Opponent::Alien),
+            _ => None
+        }
+    }
+
+    /// The sprite sheet the opponent is drawn from. Both have the same clips (`Ready`, `Winding`,
+    /// `Pitching` and `Not Ready`), so either can drive `PitcherThink`.
+    pub fn sheet(&self) -> &'static str {
+        match *self {
+            Opponent::Machine => "pitching-machine",
+            Opponent::Alien => "pitcher",
+        }
+    }
+
+    /// How much to scale by on top of `layout.pitcher_scale`. The alien was drawn at four times
+    /// the size of the machine.
+    pub fn scale(&self) -> f32 {
+        match *self {
+            Opponent::Machine => 1.,
+            Opponent::Alien => 0.25,
+        }
+    }
+}
+
+/// Positions are `(x, y)` in pixels, written `[x, y]` in the file. The meter and the text are
+/// on the screen (given for a `LAYOUT_SIZE` window, and moved to fit the real one with
+/// `WindowConfig::place`), everything else is in the world (see `camera`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// Where the middle of the background goes.
+    pub background: (f32, f32),
+    pub pitcher: (f32, f32),
+    pub pitcher_scale: f32,
+    /// The batter (an alien, like everyone else out here) stands behind the handle of the bat...
+    pub batter: (f32, f32),
+    /// ...with a cap on.
+    pub cap: (f32, f32),
+    /// Ships full of fans, out past the outfield.
+    pub stands: Vec<(f32, f32)>,
+    pub stands_scale: f32,
+    /// Where the ball leaves the pitching machine.
+    pub release_point: (f32, f32),
+    /// Where the handle of the bat sits.
+    pub bat: (f32, f32),
+    pub meter: (f32, f32),
+    /// Where the pointer sits with the meter half full.
+    pub pointer: (f32, f32),
+    /// How far the pointer moves either way from the middle of the meter.
+    pub pointer_travel: f32,
+    pub score: (f32, f32),
+    pub count: (f32, f32),
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            background: (512., 384.),
+            pitcher: (512., 530.),
+            pitcher_scale: 2.,
+            batter: (450., 717.),
+            cap: (451., 658.),
+            stands: vec![(160., -700.), (512., -1100.), (864., -700.)],
+            stands_scale: 2.,
+            release_point: (512., 560.),
+            bat: (480., 692.),
+            meter: (200., 700.),
+            pointer: (200., 730.),
+            pointer_travel: 120.,
+            score: (20., 20.),
+            count: (700., 20.),
+        }
+    }
+}
+
+/// Everything but the window goes into replays (see `recording`), so serializing leaves it out.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// `easy`, `normal` or `hard`: picks the pitch mix (see `pitches::Difficulty`).
+    pub difficulty: Difficulty,
+    /// `machine` or `alien`.
+    pub opponent: Opponent,
+    #[serde(skip_serializing)]
+    pub window: WindowConfig,
+    pub timing: TimingConfig,
+    pub meter: MeterModel,
+    pub layout: LayoutConfig,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    /// Not valid TOML, or a key has the wrong type or doesn't exist (toml's message names it).
+    Parse(String),
+    Invalid { key: String, reason: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::Io(ref e) => write!(f, "couldn't read config: {}", e),
+            ConfigError::Parse(ref msg) => write!(f, "bad config: {}", msg),
+            ConfigError::Invalid { ref key, ref reason } => write!(f, "bad config value for `{}`: {}", key, reason),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn description(&self) -> &str {
+        match *self {
+            ConfigError::Io(_) => "config io error",
+            ConfigError::Parse(_) => "config parse error",
+            ConfigError::Invalid { .. } => "invalid config value",
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+fn invalid(key: &str, reason: &str) -> ConfigError {
+    ConfigError::Invalid { key: key.to_string(), reason: reason.to_string() }
+}
+
+impl GameConfig {
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let config: GameConfig = toml::from_str(text).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Self::parse(&text)
+    }
+
+    /// Check for values that would parse fine but break the game.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.window.width == 0 {
+            return Err(invalid("window.width", "must be more than 0"));
+        }
+        if self.window.height == 0 {
+            return Err(invalid("window.height", "must be more than 0"));
+        }
+        if self.timing.tick_rate == 0 {
+            return Err(invalid("timing.tick_rate", "must be more than 0"));
+        }
+
+        let times = [
+            ("timing.windup_min_ms", self.timing.windup_min_ms),
+            ("timing.windup_range_ms", self.timing.windup_range_ms),
+            ("timing.call_hold_ms", self.timing.call_hold_ms),
+        ];
+        for &(key, value) in &times {
+            if value.is_nan() || value < 0. {
+                return Err(invalid(key, "can't be negative"));
+            }
+        }
+        if self.meter.period_ms.is_nan() || self.meter.period_ms <= 0. {
+            return Err(invalid("meter.period_ms", "must be more than 0"));
+        }
+        if self.meter.sweet_spot.is_nan() || self.meter.sweet_spot < 0. || self.meter.sweet_spot > 1. {
+            return Err(invalid("meter.sweet_spot", "must be between 0 and 1"));
+        }
+        if self.meter.perfect_window.is_nan() || self.meter.perfect_window < 0. {
+            return Err(invalid("meter.perfect_window", "can't be negative"));
+        }
+        if self.meter.good_window.is_nan() || self.meter.good_window < self.meter.perfect_window {
+            return Err(invalid("meter.good_window", "can't be smaller than `meter.perfect_window`"));
+        }
+        if self.layout.pitcher_scale.is_nan() || self.layout.pitcher_scale <= 0. {
+            return Err(invalid("layout.pitcher_scale", "must be more than 0"));
+        }
+        if self.layout.stands_scale.is_nan() || self.layout.stands_scale <= 0. {
+            return Err(invalid("layout.stands_scale", "must be more than 0"));
+        }
+        Ok(())
+    }
+}
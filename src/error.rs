@@ -0,0 +1,62 @@
+//! Everything that can go wrong getting the game's assets off the disk.
+//!
+//! Each of these names the file (and the tag, for a missing animation) it's about, and turns into
+//! a ggez `GameError` so it can come out of `MainState::new` with a message worth reading.
+
+use std::error::Error;
+use std::fmt;
+
+use ggez::GameError;
+
+use sheets::SheetError;
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssetError {
+    /// A file the game needs isn't there.
+    Missing(String),
+    /// A file is there, but couldn't be read.
+    Unreadable { path: String, reason: String },
+    /// A sprite sheet's JSON isn't the Aseprite export the game expects.
+    MalformedSheet { path: String, reason: String },
+    /// A sheet doesn't have an animation the systems play.
+    MissingClip { path: String, tag: String },
+    Sheet(SheetError),
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AssetError::Missing(ref path) => write!(f, "{} is missing", path),
+            AssetError::Unreadable { ref path, ref reason } => write!(f, "can't read {}: {}", path, reason),
+            AssetError::MalformedSheet { ref path, ref reason } =>
+                write!(f, "{} isn't a sprite sheet: {}", path, reason),
+            AssetError::MissingClip { ref path, ref tag } => write!(f, "{} has no animation tagged `{}`", path, tag),
+            AssetError::Sheet(ref e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for AssetError {
+    fn description(&self) -> &str {
+        match *self {
+            AssetError::Missing(_) => "missing asset",
+            AssetError::Unreadable { .. } => "unreadable asset",
+            AssetError::MalformedSheet { .. } => "malformed sprite sheet",
+            AssetError::MissingClip { .. } => "missing animation",
+            AssetError::Sheet(ref e) => e.description(),
+        }
+    }
+}
+
+impl From<SheetError> for AssetError {
+    fn from(e: SheetError) -> Self {
+        AssetError::Sheet(e)
+    }
+}
+
+impl From<AssetError> for GameError {
+    fn from(e: AssetError) -> Self {
+        GameError::ResourceLoadError(e.to_string())
+    }
+}
@@ -0,0 +1,187 @@
+//! Runs the simulation without a window (or a GPU), for CI and for poking at game logic.
+//!
+//! Input comes from either a replay or a little script of button presses, and instead of drawing
+//! anything we report when the game changed phase and how many draw commands were sent. Sounds aren't
+//! played either, just listed along with when they would have been.
+
+use std::collections::BTreeMap;
+use std::sync::mpsc::{channel, Receiver};
+
+use ggez::GameResult;
+
+use audio::SoundEvent;
+use config::GameConfig;
+use recording::Replay;
+use rules::{MatchRules, DEFAULT_DERBY_OUTS};
+use sheets::{SheetRegistry, DEFAULT_SHEETS};
+use systems::DrawCommand;
+use timestep::DEFAULT_TICK_RATE;
+use super::{ECS, GamePhase, InputState, TickData};
+
+/// Same tick rate as the windowed game.
+pub const DEFAULT_DELTA_MS: f32 = 1000. / DEFAULT_TICK_RATE as f32;
+
+/// Presses and releases the button at fixed times.
+#[derive(Clone, Debug)]
+pub struct ScriptedInput {
+    pub delta_ms: f32,
+    /// `(time_ms, down)` pairs, in order.
+    pub events: Vec<(f32, bool)>,
+    elapsed: f32,
+    down: bool,
+}
+
+impl ScriptedInput {
+    pub fn new(delta_ms: f32, events: Vec<(f32, bool)>) -> Self {
+        ScriptedInput {
+            delta_ms: delta_ms,
+            events: events,
+            elapsed: 0.,
+            down: false,
+        }
+    }
+
+    /// Parse a script like `500:down,4500:up`.
+    pub fn parse(delta_ms: f32, spec: &str) -> Result<Self, String> {
+        let mut events = vec![];
+        for item in spec.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let mut parts = item.split(':');
+            let time = parts.next().and_then(|t| t.parse::<f32>().ok());
+            let down = match parts.next() {
+                Some("down") => Some(true),
+                Some("up") => Some(false),
+                _ => None
+            };
+
+            match (time, down) {
+                (Some(time), Some(down)) => events.push((time, down)),
+                _ => return Err(format!("bad script entry `{}`, expected `<ms>:down` or `<ms>:up`", item))
+            }
+        }
+        Ok(Self::new(delta_ms, events))
+    }
+
+    /// Step up to the plate, then swing partway through the windup.
+    pub fn default_script() -> Self {
+        Self::new(DEFAULT_DELTA_MS, vec![(500., true), (4500., false)])
+    }
+
+    pub fn next_tick(&mut self) -> TickData {
+        let start = self.elapsed;
+        self.elapsed += self.delta_ms;
+
+        let was_down = self.down;
+        for &(time, down) in &self.events {
+            if time >= start && time < self.elapsed {
+                self.down = down;
+            }
+        }
+
+        let input_state = match (was_down, self.down) {
+            (false, true) => InputState::JustPressed,
+            (true, true) => InputState::Pressed,
+            (true, false) => InputState::JustReleased,
+            (false, false) => InputState::Released,
+        };
+
+        TickData::swing(self.delta_ms, input_state)
+    }
+}
+
+/// Where the ticks for a headless run come from.
+pub enum InputSource {
+    Script(ScriptedInput),
+    Replay(Replay),
+}
+
+impl InputSource {
+    fn next_tick(&mut self) -> Option<TickData> {
+        match *self {
+            InputSource::Script(ref mut script) => Some(script.next_tick()),
+            InputSource::Replay(ref mut replay) => replay.next_tick(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HeadlessReport {
+    pub ticks: usize,
+    pub elapsed_ms: f32,
+    /// `(time_ms, phase)` for every phase change, starting with the initial phase.
+    pub timeline: Vec<(f32, GamePhase)>,
+    /// Number of draw commands seen, keyed by what they draw.
+    pub draw_counts: BTreeMap<String, usize>,
+    /// `(time_ms, sound)` for every sound the systems asked for.
+    pub sounds: Vec<(f32, SoundEvent)>,
+}
+
+impl HeadlessReport {
+    pub fn print(&self) {
+        println!("Ran {} ticks ({} ms)", self.ticks, self.elapsed_ms);
+        println!("Phase timeline:");
+        for &(time, ref phase) in &self.timeline {
+            println!("  {:>10.1} ms  {:?}", time, phase);
+        }
+        println!("Draw commands:");
+        for (name, count) in &self.draw_counts {
+            println!("  {:>8}  {}", count, name);
+        }
+        println!("Sounds:");
+        for &(time, ref sound) in &self.sounds {
+            println!("  {:>10.1} ms  {:?}", time, sound);
+        }
+    }
+}
+
+fn describe(cmd: &DrawCommand) -> String {
+    match *cmd {
+        DrawCommand::DrawTransformed(ref path, _) => format!("DrawTransformed({})", path),
+        DrawCommand::DrawSpriteSheetCell(ref name, _, _) => format!("DrawSpriteSheetCell({})", name),
+        DrawCommand::DrawText(..) => "DrawText".to_string(),
+        DrawCommand::Camera(..) => "Camera".to_string(),
+    }
+}
+
+fn count_draws(rx: &Receiver<DrawCommand>, report: &mut HeadlessReport) {
+    for cmd in rx.try_iter() {
+        *report.draw_counts.entry(describe(&cmd)).or_insert(0) += 1;
+    }
+}
+
+/// Load the sheets from `resources/` and run up to `ticks` ticks (fewer if a replay runs out).
+pub fn run(seed: u32, ticks: usize, mut input: InputSource, config: &GameConfig) -> GameResult<HeadlessReport> {
+    let sheets = SheetRegistry::load("resources", DEFAULT_SHEETS)?;
+
+    let (tx, rx) = channel::<DrawCommand>();
+    let (sound_tx, sound_rx) = channel::<SoundEvent>();
+    let mut ecs = ECS::new(seed, tx, sound_tx, &sheets, MatchRules::derby(DEFAULT_DERBY_OUTS), config)?;
+
+    let mut report = HeadlessReport::default();
+    if let Some(phase) = ecs.phase() {
+        report.timeline.push((0., phase));
+    }
+
+    while report.ticks < ticks {
+        let tick = match input.next_tick() {
+            Some(tick) => tick,
+            None => break
+        };
+
+        report.elapsed_ms += tick.delta_ms;
+        report.ticks += 1;
+        ecs.tick(tick);
+        count_draws(&rx, &mut report);
+        for sound in sound_rx.try_iter() {
+            report.sounds.push((report.elapsed_ms, sound));
+        }
+
+        if let Some(phase) = ecs.phase() {
+            let changed = report.timeline.last().map_or(true, |&(_, ref last)| *last != phase);
+            if changed {
+                report.timeline.push((report.elapsed_ms, phase));
+            }
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,282 @@
+//! The best scores on this machine, kept in a little text file in the player's data directory.
+//!
+//! The file starts with a version line so the format can change without the game choking on old
+//! files, then has one score per line, best first:
+//!
+//! ```text
+//! hwd-highscores 1
+//! 1320 42 2017-04-23 ONE
+//! 870 7 2017-04-22 ???
+//! ```
+//!
+//! That's points, the seed the game was played with (so it can be replayed with `--seed`), the
+//! date, and the player's initials.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+pub const HIGH_SCORE_FILE: &'static str = "highscores.txt";
+pub const FORMAT_VERSION: u32 = 1;
+/// How many scores make the table.
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const DEFAULT_INITIALS: &'static str = "???";
+
+const HEADER: &'static str = "hwd-highscores";
+
+#[derive(Debug)]
+pub enum HighScoreError {
+    Io(io::Error),
+    /// Written by a newer version of the game than this one.
+    UnsupportedVersion(u32),
+    Corrupt { line: usize, reason: String },
+}
+
+impl fmt::Display for HighScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HighScoreError::Io(ref e) => write!(f, "couldn't read high scores: {}", e),
+            HighScoreError::UnsupportedVersion(version) =>
+                write!(f, "high scores are in format {}, we only understand up to {}", version, FORMAT_VERSION),
+            HighScoreError::Corrupt { line, ref reason } => write!(f, "bad high score on line {}: {}", line, reason),
+        }
+    }
+}
+
+impl Error for HighScoreError {
+    fn description(&self) -> &str {
+        match *self {
+            HighScoreError::Io(_) => "high score io error",
+            HighScoreError::UnsupportedVersion(_) => "unsupported high score format",
+            HighScoreError::Corrupt { .. } => "corrupt high score file",
+        }
+    }
+}
+
+impl From<io::Error> for HighScoreError {
+    fn from(e: io::Error) -> Self {
+        HighScoreError::Io(e)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScore {
+    pub points: u32,
+    pub seed: u32,
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    pub initials: String,
+}
+
+impl HighScore {
+    pub fn new(points: u32, seed: u32, date: &str, initials: &str) -> Self {
+        HighScore {
+            points: points,
+            seed: seed,
+            date: date.to_string(),
+            initials: clean_initials(initials),
+        }
+    }
+}
+
+/// Up to three letters or digits, upper case, so they always fit in one whitespace separated field.
+pub fn clean_initials(initials: &str) -> String {
+    let cleaned: String = initials.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_uppercase())
+        .take(3)
+        .collect();
+    if cleaned.is_empty() { DEFAULT_INITIALS.to_string() } else { cleaned }
+}
+
+/// Today's (UTC) date as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Days since 1970-01-01 to a (year, month, day). See
+/// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = (if z >= 0 { z } else { z - 146096 }) / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + (if month <= 2 { 1 } else { 0 });
+    (year, month, day)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScoreTable {
+    /// Best first.
+    pub entries: Vec<HighScore>,
+    pub capacity: usize,
+}
+
+impl HighScoreTable {
+    pub fn new(capacity: usize) -> Self {
+        HighScoreTable { entries: vec![], capacity: capacity }
+    }
+
+    pub fn qualifies(&self, points: u32) -> bool {
+        points > 0 && (self.entries.len() < self.capacity || self.entries.iter().any(|e| points > e.points))
+    }
+
+    /// Add a score, returning its place in the table (0 is the top) if it made the cut. Ties go
+    /// to whoever got there first.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.points) {
+            return None;
+        }
+        let rank = self.entries.iter().position(|e| entry.points > e.points).unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(self.capacity);
+        Some(rank)
+    }
+
+    pub fn parse(text: &str, capacity: usize) -> Result<Self, HighScoreError> {
+        let mut lines = text.lines().enumerate().filter(|&(_, line)| !line.trim().is_empty());
+
+        let version = match lines.next() {
+            None => return Ok(Self::new(capacity)),
+            Some((_, header)) => {
+                let mut parts = header.split_whitespace();
+                match (parts.next(), parts.next().and_then(|v| v.parse::<u32>().ok())) {
+                    (Some(HEADER), Some(version)) => version,
+                    _ => return Err(HighScoreError::Corrupt {
+                        line: 1,
+                        reason: format!("expected `{} <version>`", HEADER),
+                    })
+                }
+            }
+        };
+
+        if version > FORMAT_VERSION {
+            return Err(HighScoreError::UnsupportedVersion(version));
+        }
+
+        let mut table = Self::new(capacity);
+        for (idx, line) in lines {
+            let entry = parse_entry(line).map_err(|reason| HighScoreError::Corrupt {
+                line: idx + 1,
+                reason: reason,
+            })?;
+            table.insert(entry);
+        }
+        Ok(table)
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut out = format!("{} {}\n", HEADER, FORMAT_VERSION);
+        for entry in &self.entries {
+            out.push_str(&format!("{} {} {} {}\n", entry.points, entry.seed, entry.date, entry.initials));
+        }
+        out
+    }
+}
+
+fn parse_entry(line: &str) -> Result<HighScore, String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() != 4 {
+        return Err(format!("expected `<points> <seed> <date> <initials>`, got `{}`", line));
+    }
+    let points = fields[0].parse::<u32>().map_err(|_| format!("bad points `{}`", fields[0]))?;
+    let seed = fields[1].parse::<u32>().map_err(|_| format!("bad seed `{}`", fields[1]))?;
+    Ok(HighScore::new(points, seed, fields[2], fields[3]))
+}
+
+
+/// The table plus where it lives on disk, handed from scene to scene so the results screen can
+/// add to it and the title screen can show it off.
+pub struct HighScores {
+    pub table: HighScoreTable,
+    /// Who's playing, for the scores recorded this session.
+    pub initials: String,
+    path: Option<PathBuf>,
+}
+
+impl HighScores {
+    /// A table that's never saved.
+    pub fn in_memory(initials: &str) -> Self {
+        HighScores {
+            table: HighScoreTable::new(MAX_HIGH_SCORES),
+            initials: clean_initials(initials),
+            path: None,
+        }
+    }
+
+    /// Load the table from `dir`, starting fresh if there isn't one yet.
+    ///
+    /// A file we can't make sense of is reported and moved aside (to `highscores.txt.bad`) rather
+    /// than being overwritten by the next save.
+    pub fn load(dir: &Path, initials: &str) -> Self {
+        let path = dir.join(HIGH_SCORE_FILE);
+        let mut scores = Self::in_memory(initials);
+
+        match read_table(&path) {
+            Ok(Some(table)) => scores.table = table,
+            Ok(None) => (),
+            Err(e) => {
+                println!("Ignoring high scores in {}: {}", path.display(), e);
+                let aside = path.with_extension("txt.bad");
+                if let Err(e) = fs::rename(&path, &aside) {
+                    println!("Couldn't move {} aside: {}", path.display(), e);
+                }
+            }
+        }
+
+        scores.path = Some(path);
+        scores
+    }
+
+    /// Add a score for the current player and save the table if it made it in.
+    pub fn record(&mut self, points: u32, seed: u32) -> Option<usize> {
+        let rank = self.table.insert(HighScore::new(points, seed, &today(), &self.initials));
+        if rank.is_some() {
+            if let Err(e) = self.save() {
+                println!("Couldn't save high scores: {}", e);
+            }
+        }
+        rank
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(())
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // write the whole thing somewhere else first so a crash can't leave half a table behind
+        let tmp = path.with_extension("txt.tmp");
+        File::create(&tmp)?.write_all(self.table.serialize().as_bytes())?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Lines for showing the table on screen.
+    pub fn lines(&self) -> Vec<String> {
+        self.table.entries.iter().enumerate()
+            .map(|(i, e)| format!("{:>2}. {:<3} {:>6}  {}", i + 1, e.initials, e.points, e.date))
+            .collect()
+    }
+}
+
+fn read_table(path: &Path) -> Result<Option<HighScoreTable>, HighScoreError> {
+    let mut text = String::new();
+    match File::open(path) {
+        Ok(mut file) => file.read_to_string(&mut text)?,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    HighScoreTable::parse(&text, MAX_HIGH_SCORES).map(Some)
+}
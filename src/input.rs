@@ -0,0 +1,184 @@
+//! Turning keys, mouse buttons and gamepad buttons into `Action`s.
+//!
+//! Bindings are read from a little config file, one action per line:
+//!
+//! ```text
+//! # action = binding, binding, ...
+//! swing = key:Space, mouse:Left, pad:A
+//! pause = key:Escape, pad:Start
+//! ```
+//!
+//! Key names are SDL's (`Space`, `Return`, `Left Shift`...), gamepad buttons are SDL's game
+//! controller names (`a`, `b`, `start`, `dpup`...), and mouse buttons are `Left`, `Middle`,
+//! `Right`, `X1` or `X2`. A binding can appear under more than one action, which is how Space both
+//! swings and confirms.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use ggez::event::{Button, Keycode, MouseButton};
+
+use super::{Action, ACTIONS};
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Binding {
+    Key(Keycode),
+    Mouse(MouseButton),
+    Pad(Button),
+}
+
+impl Binding {
+    /// Parse something like `key:Space`, `mouse:Left` or `pad:a`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut parts = spec.splitn(2, ':');
+        let device = parts.next().unwrap_or("").trim();
+        let name = parts.next().unwrap_or("").trim();
+
+        let binding = match device {
+            "key" => Keycode::from_name(name).map(Binding::Key),
+            "mouse" => parse_mouse_button(name).map(Binding::Mouse),
+            "pad" => Button::from_string(&name.to_lowercase()).map(Binding::Pad),
+            _ => return Err(format!("bad binding `{}`, expected `key:<name>`, `mouse:<name>` or `pad:<name>`", spec))
+        };
+        binding.ok_or_else(|| format!("unknown {} `{}`", device, name))
+    }
+}
+
+fn parse_mouse_button(name: &str) -> Option<MouseButton> {
+    match name.to_lowercase().as_ref() {
+        "left" => Some(MouseButton::Left),
+        "middle" => Some(MouseButton::Middle),
+        "right" => Some(MouseButton::Right),
+        "x1" => Some(MouseButton::X1),
+        "x2" => Some(MouseButton::X2),
+        _ => None
+    }
+}
+
+fn parse_action(name: &str) -> Option<Action> {
+    match name {
+        "swing" => Some(Action::Swing),
+        "pause" => Some(Action::Pause),
+        "confirm" => Some(Action::Confirm),
+        "back" => Some(Action::Back),
+        "mute" => Some(Action::Mute),
+        "volume_up" => Some(Action::VolumeUp),
+        "volume_down" => Some(Action::VolumeDown),
+        _ => None
+    }
+}
+
+/// Which bindings trigger which actions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputMap {
+    bindings: Vec<(Binding, Action)>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        InputMap {
+            bindings: vec![
+                (Binding::Key(Keycode::Space), Action::Swing),
+                (Binding::Mouse(MouseButton::Left), Action::Swing),
+                (Binding::Pad(Button::A), Action::Swing),
+                (Binding::Key(Keycode::Escape), Action::Pause),
+                (Binding::Pad(Button::Start), Action::Pause),
+                (Binding::Key(Keycode::Space), Action::Confirm),
+                (Binding::Key(Keycode::Return), Action::Confirm),
+                (Binding::Mouse(MouseButton::Left), Action::Confirm),
+                (Binding::Pad(Button::A), Action::Confirm),
+                (Binding::Key(Keycode::Backspace), Action::Back),
+                (Binding::Pad(Button::B), Action::Back),
+                (Binding::Key(Keycode::M), Action::Mute),
+                (Binding::Key(Keycode::Equals), Action::VolumeUp),
+                (Binding::Key(Keycode::Minus), Action::VolumeDown),
+            ]
+        }
+    }
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        InputMap { bindings: vec![] }
+    }
+
+    pub fn bind(&mut self, binding: Binding, action: Action) {
+        if !self.bindings.contains(&(binding, action)) {
+            self.bindings.push((binding, action));
+        }
+    }
+
+    /// The actions `binding` triggers, in `ACTIONS` order.
+    pub fn actions(&self, binding: &Binding) -> Vec<Action> {
+        ACTIONS.iter()
+            .filter(|&&action| self.bindings.contains(&(*binding, action)))
+            .cloned()
+            .collect()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut map = Self::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            let action = parse_action(name)
+                .ok_or_else(|| format!("line {}: unknown action `{}`", idx + 1, name))?;
+            let specs = parts.next().ok_or_else(|| format!("line {}: expected `{} = <bindings>`", idx + 1, name))?;
+
+            for spec in specs.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                let binding = Binding::parse(spec).map_err(|e| format!("line {}: {}", idx + 1, e))?;
+                map.bind(binding, action);
+            }
+        }
+        Ok(map)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Self::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// An `InputMap` plus whatever is being held down, so that holding two buttons bound to the same
+/// action (or key repeat) doesn't press it twice, and letting go of one doesn't release it.
+pub struct Controls {
+    pub map: InputMap,
+    held: Vec<Binding>,
+}
+
+impl Controls {
+    pub fn new(map: InputMap) -> Self {
+        Controls { map: map, held: vec![] }
+    }
+
+    fn is_held(&self, action: Action) -> bool {
+        self.held.iter().any(|binding| self.map.actions(binding).contains(&action))
+    }
+
+    /// The actions that went down because of `binding`.
+    pub fn press(&mut self, binding: Binding) -> Vec<Action> {
+        if self.held.contains(&binding) {
+            return vec![];
+        }
+        let pressed = self.map.actions(&binding).into_iter().filter(|&action| !self.is_held(action)).collect();
+        self.held.push(binding);
+        pressed
+    }
+
+    /// The actions that came up because of `binding`.
+    pub fn release(&mut self, binding: Binding) -> Vec<Action> {
+        if !self.held.contains(&binding) {
+            return vec![];
+        }
+        self.held.retain(|held| *held != binding);
+        self.map.actions(&binding).into_iter().filter(|&action| !self.is_held(action)).collect()
+    }
+}
@@ -0,0 +1,312 @@
+//! Home World Derby: the simulation side of the game.
+//!
+//! Everything in here runs without a window. The `ld38` binary owns the ggez side of things,
+//! feeding `TickData` into the `ECS` and drawing whatever `DrawCommand`s come back out.
+
+extern crate ggez;
+extern crate specs;
+extern crate rand;
+extern crate omn_labs;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate toml;
+extern crate flate2;
+extern crate serde_json;
+
+
+pub mod ase;
+pub mod audio;
+pub mod camera;
+pub mod components;
+pub mod config;
+pub mod error;
+pub mod headless;
+pub mod highscores;
+pub mod input;
+pub mod meter;
+pub mod pitches;
+pub mod recording;
+pub mod render;
+pub mod rules;
+pub mod scenes;
+pub mod score;
+pub mod sheets;
+pub mod systems;
+pub mod timestep;
+pub mod watch;
+
+use std::sync::mpsc::Sender;
+
+use ggez::GameResult;
+
+use rand::{SeedableRng, XorShiftRng};
+use specs::Join;
+
+use omn_labs::sprites::PlayMode;
+use audio::SoundEvent;
+use camera::Camera;
+use config::GameConfig;
+use error::AssetError;
+use rules::MatchRules;
+use score::Score;
+use sheets::{LiveClips, SheetRegistry};
+pub use systems::DrawCommand;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GamePhase {
+    WaitingForPlayer,
+    PlayerReady,
+    Windup, // variable duration
+    Pitching, // fixed length
+    BallInFlight,
+
+    // different results of a swing
+    Foul,
+    HomeRun,
+    Hit,
+    Miss
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputState {
+    JustPressed,
+    Pressed,
+    JustReleased,
+    Released
+}
+
+/// Things the player can ask for, whatever they're bound to (see `input::InputMap`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    Swing,
+    Pause,
+    Confirm,
+    Back,
+    Mute,
+    VolumeUp,
+    VolumeDown,
+}
+
+/// Every action, in the order they're handed out when one binding triggers several.
+pub const ACTIONS: [Action; 7] = [
+    Action::Swing,
+    Action::Pause,
+    Action::Confirm,
+    Action::Back,
+    Action::Mute,
+    Action::VolumeUp,
+    Action::VolumeDown,
+];
+
+/// An `InputState` for each `Action`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionStates {
+    states: [InputState; 7],
+}
+
+impl ActionStates {
+    pub fn new() -> Self {
+        ActionStates { states: [InputState::Released; 7] }
+    }
+
+    pub fn get(&self, action: Action) -> InputState {
+        self.states[action as usize]
+    }
+
+    pub fn set(&mut self, action: Action, state: InputState) {
+        self.states[action as usize] = state;
+    }
+
+    /// Release everything, eg. so a press doesn't carry over a pause.
+    pub fn clear(&mut self) {
+        self.states = [InputState::Released; 7];
+    }
+
+    /// If an action was "just" anything last tick, and the state hasn't been updated by an event
+    /// handler since, "decay" it to the non-"just" version.
+    pub fn decay(&mut self, last: &ActionStates) {
+        for &action in &ACTIONS {
+            match (last.get(action), self.get(action)) {
+                (InputState::JustPressed, InputState::JustPressed) => self.set(action, InputState::Pressed),
+                (InputState::JustReleased, InputState::JustReleased) => self.set(action, InputState::Released),
+                _ => ()
+            }
+        }
+    }
+}
+
+pub type Delta = f32;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TickData {
+    pub delta_ms: Delta,
+    pub input: ActionStates,
+}
+
+impl TickData {
+    pub fn new() -> Self {
+        Self {
+            input: ActionStates::new(),
+            delta_ms: 0.,
+        }
+    }
+
+    /// A tick where only the swing button is doing anything, which is all the simulation cares
+    /// about.
+    pub fn swing(delta_ms: Delta, state: InputState) -> Self {
+        let mut tick = Self::new();
+        tick.delta_ms = delta_ms;
+        tick.input.set(Action::Swing, state);
+        tick
+    }
+}
+
+/// Build the RNG every system draws from. The same seed (plus the same input) always plays out the
+/// same game.
+pub fn seeded_rng(seed: u32) -> XorShiftRng {
+    // xorshift can't be seeded with all zeros, so mix in some constants.
+    XorShiftRng::from_seed([seed, seed ^ 0x9E37_79B9, 0x7F4A_7C15, 0x2545_F491])
+}
+
+pub struct ECS {
+    pub planner: specs::Planner<TickData>,
+    pub render_tx: Sender<DrawCommand>,
+    pub sound_tx: Sender<SoundEvent>,
+    pub seed: u32,
+}
+
+impl ECS {
+    /// Fails if `sheets` is missing one of the animations the systems play.
+    pub fn new(seed: u32,
+               render_tx: Sender<DrawCommand>,
+               sound_tx: Sender<SoundEvent>,
+               sheets: &SheetRegistry,
+               rules: MatchRules,
+               config: &GameConfig) -> GameResult<ECS> {
+
+        let (bat_clips, pitcher_clips, power_meter_clips, pointer_clips) = Self::check_sheets(sheets, config)?;
+
+        let mut world = specs::World::new();
+        world.register::<components::Pitcher>();
+        world.register::<components::PowerMeter>();
+        world.register::<components::Batter>();
+        world.register::<components::Bat>();
+        world.register::<components::Ball>();
+        world.register::<components::GameFlow>();
+        world.register::<components::Umpire>();
+        world.add_resource(seeded_rng(seed));
+        world.add_resource(rules);
+        world.add_resource(Score::default());
+        world.add_resource(config.clone());
+        world.add_resource(Camera::plate(config.window.size()));
+
+        // entities are created by combining various components via the world
+        world.create_now()
+            .with(components::Pitcher {
+                action_ttl: 0., // will get set by system when we enter the winding phase
+                active_clip: pitcher_clips.create("Ready", PlayMode::Loop),
+                pitch: None,
+            })
+            .with(components::Batter { })
+            .with(components::Bat {
+                swinging: false,
+                bbox: systems::bat_bbox(&config.layout),
+                power: 0.,
+                grade: None,
+                swing_ms: 0.,
+                active_clip: bat_clips.create("Idle", PlayMode::Loop),
+            })
+            .with(components::PowerMeter {
+                active_clip: power_meter_clips.create("No Bar", PlayMode::Hold),
+                // `check_sheets` made sure it's there
+                pointer_clip: pointer_clips.create("Default", PlayMode::Loop).unwrap(),
+                power_level: 0.,
+                time: 0.
+            })
+            .with(components::GameFlow { active: GamePhase::WaitingForPlayer })
+            .with(components::Umpire { call_ttl: 0. })
+            .build();
+
+        let mut plan = specs::Planner::new(world, 1);
+
+        let power_sys = systems::PowerMeterSys {
+            clips: power_meter_clips.clone()
+        };
+        plan.add_system(power_sys, "power", 10);
+
+        let batter_sys = systems::BatterThink { clips: bat_clips.clone() };
+        plan.add_system(batter_sys, "batter", 10);
+
+        let pitch_sys = systems::PitcherThink {
+            clips: pitcher_clips.clone(),
+            sound_tx: sound_tx.clone(),
+        };
+        plan.add_system(pitch_sys, "pitcher", 15);
+
+        plan.add_system(systems::BallPhysics { sound_tx: sound_tx.clone() }, "ball", 20);
+
+        plan.add_system(systems::UmpireSys { sound_tx: sound_tx.clone() }, "umpire", 25);
+
+        plan.add_system(systems::CameraSys, "camera", 30);
+
+        let render_sys = systems::Render { tx: render_tx.clone() };
+        plan.add_system(render_sys, "render", 100);
+
+        // higher priorities go first, so this comes after the render and ends up on top
+        let hud_sys = systems::Hud { tx: render_tx.clone() };
+        plan.add_system(hud_sys, "hud", 90);
+
+        Ok(ECS {
+            planner: plan,
+            render_tx: render_tx,
+            sound_tx: sound_tx,
+            seed: seed,
+        })
+    }
+
+    /// Make sure `sheets` has every animation a game played with `config` needs, handing back the
+    /// bat's, the opponent's, the power meter's and its pointer's clips.
+    pub fn check_sheets(sheets: &SheetRegistry, config: &GameConfig)
+                        -> Result<(LiveClips, LiveClips, LiveClips, LiveClips), AssetError> {
+        Ok((sheets.require_clips("bat", systems::BAT_CLIPS)?,
+            sheets.require_clips(config.opponent.sheet(), systems::PITCHER_CLIPS)?,
+            sheets.require_clips("bar", systems::METER_CLIPS)?,
+            sheets.require_clips("pointer", systems::POINTER_CLIPS)?))
+    }
+
+    /// The phase the game is in right now.
+    pub fn phase(&mut self) -> Option<GamePhase> {
+        let world = self.planner.mut_world();
+        let flows = world.read::<components::GameFlow>();
+        let phase = (&flows).iter().next().map(|flow| flow.active.clone());
+        phase
+    }
+
+    /// The count, outs and so on as of the last call.
+    pub fn rules(&mut self) -> MatchRules {
+        let world = self.planner.mut_world();
+        let rules = world.read_resource::<MatchRules>().clone();
+        rules
+    }
+
+    pub fn score(&mut self) -> Score {
+        let world = self.planner.mut_world();
+        let score = world.read_resource::<Score>().clone();
+        score
+    }
+
+    /// Where the camera is looking as of the last tick.
+    pub fn camera(&mut self) -> Camera {
+        let world = self.planner.mut_world();
+        let camera = world.read_resource::<Camera>().clone();
+        camera
+    }
+
+    pub fn tick(&mut self, tick_data: TickData) -> bool {
+        self.planner.dispatch(tick_data);
+        self.planner.wait();
+        true
+    }
+}
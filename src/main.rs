@@ -1,294 +1,564 @@
 extern crate ggez;
-extern crate specs;
 extern crate rand;
 extern crate omn_labs;
-
-
-mod components;
-mod systems;
-
+extern crate ld38;
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::rc::Rc;
+use std::thread;
 use std::time::Duration;
-use std::sync::mpsc::{channel, Receiver, Sender};
 
+use ggez::audio;
 use ggez::conf;
 use ggez::event::*;
-use ggez::timer;
 use ggez::GameResult;
 use ggez::Context;
 use ggez::graphics;
 
+use rand::Rng;
+
 use omn_labs::assets::AssetBundle;
-use omn_labs::sprites::{SpriteSheetData, PlayMode};
-use systems::DrawCommand;
-
-#[derive(Clone, Debug, PartialEq)]
-pub enum GamePhase {
-    WaitingForPlayer,
-    PlayerReady,
-    Windup, // variable duration
-    Pitching, // fixed length
-    BallInFlight,
-
-    // different results of a swing
-    Foul,
-    HomeRun,
-    Hit,
-    Miss
+use ld38::{Action, ECS};
+use ld38::camera::Camera;
+use ld38::audio::{AudioBackend, AudioSettings, NullAudio, SoundEvent, SOUND_EVENTS, VOLUME_STEP};
+use ld38::config::{ConfigError, GameConfig, Opponent};
+use ld38::error::AssetError;
+use ld38::headless;
+use ld38::highscores::{HighScores, DEFAULT_INITIALS};
+use ld38::input::{Binding, Controls, InputMap};
+use ld38::recording::{Replay, TickRecorder};
+use ld38::render::{draw_spec, DrawSpec};
+use ld38::scenes::{GameplayScene, Scene, SceneEvent, SceneStack, TitleScene};
+use ld38::sheets::{SheetRegistry, DEFAULT_SHEETS};
+use ld38::systems::DrawCommand;
+use ld38::timestep::{duration_ms, FixedTimestep};
+use ld38::watch::DirWatcher;
+
+/// Settings used when `--config` isn't given.
+const DEFAULT_CONFIG: &'static str = "resources/config.toml";
+
+/// Bindings used when `--controls` isn't given.
+const DEFAULT_CONTROLS: &'static str = "resources/controls.cfg";
+
+/// Where the images and sprite sheets live.
+const RESOURCES_DIR: &'static str = "resources";
+
+/// Images drawn on their own, rather than cut from a sprite sheet.
+const IMAGES: &'static [&'static str] = &["background.png", "ball.png"];
+
+/// Size (in points) of the HUD font.
+const FONT_SIZE: u32 = 24;
+
+fn draw_param(spec: DrawSpec) -> graphics::DrawParam {
+    graphics::DrawParam {
+        src: spec.src,
+        dest: spec.dest,
+        rotation: spec.rotation,
+        scale: spec.scale,
+        offset: spec.offset,
+        ..Default::default()
+    }
 }
 
-
-#[derive(Clone, Debug, PartialEq)]
-pub enum InputState {
-    JustPressed,
-    Pressed,
-    JustReleased,
-    Released
+/// Plays sounds through ggez, one `Source` per event.
+struct GgezAudio {
+    sources: Vec<(SoundEvent, audio::Source)>,
 }
 
-pub type Delta = f32;
-
-#[derive(Clone, Debug, PartialEq)]
-pub struct TickData {
-    delta_ms: Delta,
-    /// We only have one button to track (I think) so we can get away with a single member to
-    /// track the state.
-    input_state: InputState,
-}
-
-impl TickData {
-    pub fn new() -> Self {
-        Self {
-            input_state: InputState::Released,
-            delta_ms: 0.,
+impl GgezAudio {
+    fn new(ctx: &mut Context) -> GameResult<Self> {
+        let mut sources = vec![];
+        for event in SOUND_EVENTS.iter() {
+            sources.push((event.clone(), audio::Source::new(ctx, event.file())?));
         }
+        Ok(GgezAudio { sources: sources })
     }
 }
 
-pub struct ECS {
-    pub planner: specs::Planner<TickData>,
-    pub render_tx: Sender<DrawCommand>,
-}
-
-impl ECS {
-    pub fn new(render_tx: Sender<DrawCommand>,
-               bat_sheet: &SpriteSheetData,
-               pitcher_sheet: &SpriteSheetData,
-               power_meter_sheet: &SpriteSheetData,
-               pointer_sheet: &SpriteSheetData) -> ECS {
-
-        let mut world = specs::World::new();
-        world.register::<components::Pitcher>();
-        world.register::<components::PowerMeter>();
-        world.register::<components::Batter>();
-        world.register::<components::Bat>();
-        world.register::<components::Ball>();
-        world.register::<components::GameFlow>();
-
-        // entities are created by combining various components via the world
-        world.create_now()
-            .with(components::Pitcher {
-                action_ttl: 0., // will get set by system when we enter the winding phase
-                active_clip: Some(pitcher_sheet.clips.create("Ready", PlayMode::Loop).unwrap()),
-            })
-            .with(components::Batter { })
-            .with(components::PowerMeter {
-                active_clip: Some(power_meter_sheet.clips.create("No Bar", PlayMode::Hold).unwrap()),
-                pointer_clip: pointer_sheet.clips.create("Default", PlayMode::Loop).unwrap(),
-                power_level: 0.,
-                time: 0.
-            })
-            .with(components::GameFlow { active: GamePhase::WaitingForPlayer })
-            .build();
-
-        let mut plan = specs::Planner::new(world, 1);
-
-        let power_sys = systems::PowerMeterSys {
-            clips: power_meter_sheet.clips.clone()
-        };
-        plan.add_system(power_sys, "power", 10);
-
-        let batter_sys = systems::BatterThink { clips: bat_sheet.clips.clone() };
-        plan.add_system(batter_sys, "batter", 10);
-
-        let pitch_sys = systems::PitcherThink {
-            clips: pitcher_sheet.clips.clone(),
-        };
-        plan.add_system(pitch_sys, "pitcher", 15);
-
-        let render_sys = systems::Render { tx: render_tx.clone() };
-        plan.add_system(render_sys, "render", 100);
-
-        ECS {
-            planner: plan,
-            render_tx: render_tx
+impl AudioBackend for GgezAudio {
+    fn play(&mut self, event: &SoundEvent) {
+        if let Some(&(_, ref source)) = self.sources.iter().find(|&&(ref e, _)| e == event) {
+            if let Err(e) = source.play() {
+                println!("Couldn't play {:?}: {}", event, e);
+            }
         }
     }
 
-    pub fn tick(&mut self, tick_data: TickData) -> bool {
-        self.planner.dispatch(tick_data);
-        self.planner.wait();
-        true
+    fn apply(&mut self, settings: &AudioSettings) {
+        for &mut (_, ref mut source) in &mut self.sources {
+            source.set_volume(settings.effective_volume());
+        }
     }
 }
 
 struct MainState {
     assets: AssetBundle,
-    last_tick: TickData,
-    current_tick: TickData,
-    ecs: ECS,
-    render_rx: Receiver<DrawCommand>,
-    pitcher_sheet: SpriteSheetData,
-    power_meter_sheet: SpriteSheetData,
-    pointer_sheet: SpriteSheetData,
+    /// Sprite sheet images read from `.ase` files, and anything reloaded since, in place of the
+    /// `AssetBundle`'s copy.
+    atlases: HashMap<String, graphics::Image>,
+    font: graphics::Font,
+    /// Rendered text, so we aren't rasterizing the same strings every frame. Only lines drawn in
+    /// the last frame are kept, so a score that keeps changing doesn't leave every old one behind.
+    texts: HashMap<String, graphics::Text>,
+    sheets: Rc<RefCell<SheetRegistry>>,
+    scenes: SceneStack,
+    controls: Controls,
+    config: Rc<GameConfig>,
+    timestep: FixedTimestep,
+    audio: Box<AudioBackend>,
+    audio_settings: AudioSettings,
+    /// Where the audio settings get saved.
+    user_dir: PathBuf,
+    /// Set with `--watch`, to pick up edited sheets and images without restarting.
+    watcher: Option<DirWatcher>,
 }
 
 impl MainState {
-    fn new(ctx: &mut Context) -> GameResult<Self> {
-        ctx.print_resource_stats();
+    fn press(&mut self, binding: Binding) {
+        for action in self.controls.press(binding) {
+            match action {
+                // these work the same everywhere, so the scenes never see them
+                Action::Mute => self.change_audio(|settings| settings.toggle_mute()),
+                Action::VolumeUp => self.change_audio(|settings| settings.adjust(VOLUME_STEP)),
+                Action::VolumeDown => self.change_audio(|settings| settings.adjust(-VOLUME_STEP)),
+                _ => self.scenes.input(SceneEvent::Pressed(action)),
+            }
+        }
+    }
 
-        // render pipe - Sender/Receiver
-        let (tx, rx) = channel::<DrawCommand>();
+    fn change_audio<F: FnOnce(&mut AudioSettings)>(&mut self, change: F) {
+        change(&mut self.audio_settings);
+        self.audio.apply(&self.audio_settings);
+        if let Err(e) = self.audio_settings.save(&self.user_dir) {
+            println!("Couldn't save audio settings: {}", e);
+        }
+    }
 
-        let bat_sheet = SpriteSheetData::from_file("resources/bat.json");
-        let pitcher_sheet = SpriteSheetData::from_file("resources/pitching-machine.json");
-        let power_meter_sheet = SpriteSheetData::from_file("resources/bar.json");
-        let pointer_sheet = SpriteSheetData::from_file("resources/pointer.json");
+    fn release(&mut self, binding: Binding) {
+        for action in self.controls.release(binding) {
+            match action {
+                Action::Mute | Action::VolumeUp | Action::VolumeDown => (),
+                _ => self.scenes.input(SceneEvent::Released(action)),
+            }
+        }
+    }
 
-        let s = MainState {
-            assets: AssetBundle::new(ctx, &vec![
-                "background.png",
-                "pitching-machine.png",
-                "bar.png",
-                "pointer.png"
-            ]),
-            ecs: ECS::new(tx, &bat_sheet, &pitcher_sheet, &power_meter_sheet, &pointer_sheet),
-            last_tick: TickData::new(),
-            current_tick: TickData::new(),
-            render_rx: rx,
-            pitcher_sheet: pitcher_sheet,
-            power_meter_sheet: power_meter_sheet,
-            pointer_sheet: pointer_sheet,
-        };
+    /// Load whatever changed on disk again: sheets (with their clips swapped into the running
+    /// systems), the images they draw from, and the images drawn on their own. Anything that
+    /// doesn't load stays as it was.
+    fn reload(&mut self, ctx: &mut Context, changed: &[String]) {
+        for file in changed {
+            let id = self.sheets.borrow().sheet_for(file).map(|id| id.to_string());
+            let result = match id {
+                Some(id) => self.reload_sheet(ctx, &id, file),
+                None if IMAGES.contains(&file.as_str()) => self.reload_image(ctx, file),
+                None => continue,
+            };
+            match result {
+                Ok(()) => println!("Reloaded {}", file),
+                Err(e) => println!("Couldn't reload {}, keeping the old one: {}", file, e),
+            }
+        }
+    }
 
-        Ok(s)
+    fn reload_sheet(&mut self, ctx: &mut Context, id: &str, file: &str) -> GameResult<()> {
+        let mut sheets = self.sheets.borrow_mut();
+        sheets.reload(RESOURCES_DIR, file)?;
+        let entry = sheets.get(id).map_err(AssetError::from)?;
+        let image = match entry.pixels {
+            Some(ref pixels) =>
+                graphics::Image::from_rgba8(ctx, pixels.width as u16, pixels.height as u16, &pixels.rgba)?,
+            // the export's image may have changed too, or never been loaded if the sheet was an `.ase`
+            None => graphics::Image::new(ctx, format!("/{}", entry.image))?,
+        };
+        self.atlases.insert(entry.image.clone(), image);
+        Ok(())
     }
 
-    fn update_current_tick_data(&mut self, delta_ms: Delta) {
+    /// Images loaded here take the place of the `AssetBundle`'s copy.
+    fn reload_image(&mut self, ctx: &mut Context, name: &str) -> GameResult<()> {
+        let image = graphics::Image::new(ctx, format!("/{}", name))?;
+        self.atlases.insert(name.to_string(), image);
+        Ok(())
+    }
 
-        self.current_tick.delta_ms = delta_ms;
+    fn new(ctx: &mut Context, opts: Options) -> GameResult<Self> {
+        ctx.print_resource_stats();
 
-        // If the state was "just" anything last tick, and the state hasn't been updated by an event
-        // handler during this tick, we'll "decay" the state and make it the non-"just" version.
-        match self.last_tick.input_state {
-            InputState::JustPressed if self.current_tick.input_state == InputState::JustPressed => {
-                self.current_tick.input_state = InputState::Pressed
+        // better to find out about a broken sheet now than when the game starts
+        let sheets = SheetRegistry::load(RESOURCES_DIR, DEFAULT_SHEETS)?;
+        ECS::check_sheets(&sheets, &opts.config)?;
+        let assets = {
+            let mut images = IMAGES.to_vec();
+            images.extend(sheets.images());
+            for image in &images {
+                let path = Path::new(RESOURCES_DIR).join(image);
+                if !path.is_file() {
+                    return Err(AssetError::Missing(path.display().to_string()).into());
+                }
+            }
+            AssetBundle::new(ctx, &images)
+        };
+        let mut atlases = HashMap::new();
+        for (name, pixels) in sheets.pixels() {
+            let image = graphics::Image::from_rgba8(ctx, pixels.width as u16, pixels.height as u16, &pixels.rgba)?;
+            atlases.insert(name.to_string(), image);
+        }
+        let sheets = Rc::new(RefCell::new(sheets));
+        let font = graphics::Font::new(ctx, "/DejaVuSans.ttf", FONT_SIZE)?;
+        let config = Rc::new(opts.config);
+        let user_dir = ctx.filesystem.get_user_data_dir().to_path_buf();
+
+        // no sound is better than no game
+        let mut audio: Box<AudioBackend> = match GgezAudio::new(ctx) {
+            Ok(audio) => Box::new(audio),
+            Err(e) => {
+                println!("Couldn't load sounds, carrying on without them: {}", e);
+                Box::new(NullAudio::default())
             }
+        };
+        let audio_settings = AudioSettings::load(&user_dir);
+        audio.apply(&audio_settings);
+
+        // replays skip the title and go straight into the game they recorded
+        let first_scene: Box<Scene> = match opts.playback {
+            Some(replay) => Box::new(GameplayScene::from_replay(sheets.clone(), config.clone(), replay)?),
+            None => {
+                let mut title = TitleScene::new(sheets.clone(), config.clone(), opts.seed);
+                // replays don't get a say in the high scores, so only load them for real games
+                title.high_scores = Some(HighScores::load(&user_dir, &opts.initials));
+                if let Some(ref path) = opts.record {
+                    println!("Recording to {}", path);
+                    title.recorder = Some(TickRecorder::create(path, opts.seed, &config)?);
+                }
+                Box::new(title)
+            }
+        };
 
-            InputState::JustReleased if self.current_tick.input_state == InputState::JustReleased => {
-                self.current_tick.input_state = InputState::Released
-            },
-            _ => ()
+        let s = MainState {
+            assets: assets,
+            atlases: atlases,
+            font: font,
+            texts: HashMap::new(),
+            sheets: sheets,
+            scenes: SceneStack::new(first_scene),
+            controls: Controls::new(opts.controls),
+            timestep: FixedTimestep::new(config.timing.tick_rate),
+            config: config,
+            audio: audio,
+            audio_settings: audio_settings,
+            user_dir: user_dir,
+            watcher: if opts.watch { Some(DirWatcher::new(RESOURCES_DIR)) } else { None },
         };
+
+        Ok(s)
     }
 }
 
 
 impl EventHandler for MainState {
 
+    // `Controls` ignores presses of something that's already down, so key repeats are harmless
     fn key_down_event(&mut self, keycode: Keycode, _keymod: Mod, _repeat: bool) {
-        match keycode {
-            // guard to prevent key repeats on long holds
-            Keycode::Space if self.current_tick.input_state != InputState::Pressed => {
-                self.current_tick.input_state = InputState::JustPressed;
-            }
-            _ => (),
-        }
+        self.press(Binding::Key(keycode));
     }
 
     fn key_up_event(&mut self, keycode: Keycode, _keymod: Mod, _repeat: bool) {
-        match keycode {
+        self.release(Binding::Key(keycode));
+    }
 
-            Keycode::Space => {
-                self.current_tick.input_state = InputState::JustReleased;
-            }
-            _ => (),
-        }
+    fn mouse_button_down_event(&mut self, button: MouseButton, _x: i32, _y: i32) {
+        self.press(Binding::Mouse(button));
+    }
+
+    fn mouse_button_up_event(&mut self, button: MouseButton, _x: i32, _y: i32) {
+        self.release(Binding::Mouse(button));
+    }
+
+    fn controller_button_down_event(&mut self, btn: Button, _instance_id: i32) {
+        self.press(Binding::Pad(btn));
     }
 
-    fn update(&mut self, _ctx: &mut Context, _dt: Duration) -> GameResult<()> {
-        let delta_ms = _dt.subsec_nanos() as f32 / 1e6;
-        self.update_current_tick_data(delta_ms);
-//        println!("{:?}", self.current_tick);
-        self.ecs.tick(self.current_tick.clone());
-        self.last_tick = self.current_tick.clone();
+    fn controller_button_up_event(&mut self, btn: Button, _instance_id: i32) {
+        self.release(Binding::Pad(btn));
+    }
 
-        timer::sleep_until_next_frame(_ctx, 60);
+    fn update(&mut self, ctx: &mut Context, dt: Duration) -> GameResult<()> {
+        let steps = self.timestep.advance(duration_ms(dt));
+        if steps == 0 {
+            // nothing to do until the next step is due, so don't spin waiting for it
+            let wait_ms = (1. - self.timestep.alpha()) * self.timestep.step_ms;
+            thread::sleep(Duration::from_millis(wait_ms as u64));
+        }
+        for _ in 0..steps {
+            self.scenes.update(self.timestep.step_ms);
+            if self.scenes.is_empty() {
+                break;
+            }
+        }
+        for sound in self.scenes.take_sounds() {
+            self.audio.play(&sound);
+        }
+        let changed = match self.watcher {
+            Some(ref mut watcher) => watcher.poll(duration_ms(dt)),
+            None => vec![],
+        };
+        self.reload(ctx, &changed);
+
+        if self.scenes.is_empty() {
+            ctx.quit()?;
+        }
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
         graphics::clear(ctx);
-        // draw the background before all the dynamic stuff
-        let bg = self.assets.get_image(ctx, "background.png");
-
-        graphics::draw(ctx, bg, graphics::Point::new(1024. / 2., 768. / 2.), 0.)?;
 
-        for cmd in self.render_rx.try_iter() {
-            match cmd {
-                DrawCommand::DrawTransformed { path, x, y, rot , .. } => {
-                    let image = self.assets.get_image(ctx, path.as_ref());
-                    graphics::draw(ctx, image, graphics::Point::new(x, y), rot)?;
+        // every frame starts out looking at the plate, until a scene says otherwise
+        let mut camera = Camera::plate(self.config.window.size());
+        let mut drawn_texts = HashSet::new();
+        for cmd in self.scenes.draw() {
+            let (image, spec) = match cmd {
+                DrawCommand::Camera(next) => {
+                    camera = next;
+                    continue;
                 }
-                DrawCommand::DrawSpriteSheetCell(name, idx, pos, scale) => {
-                    let atlas = self.assets.get_image(ctx, name.as_ref());
-                    let w = atlas.width() as f32;
-                    let h = atlas.height() as f32;
-
-                    let maybe_cell = match name.as_ref() {
-                        "pitching-machine.png" => Some(&self.pitcher_sheet.cells[idx]),
-                        "bar.png" => Some(&self.power_meter_sheet.cells[idx]),
-                        "pointer.png" => Some(&self.pointer_sheet.cells[idx]),
-                        _ => None
+                DrawCommand::DrawTransformed(path, xform) => {
+                    let image = match self.atlases.get(&path) {
+                        Some(image) => image,
+                        None => self.assets.get_image(ctx, path.as_ref()),
                     };
+                    let spec = draw_spec(&camera.apply(&xform), image.width() as f32, image.height() as f32, None);
+                    (image, spec)
+                }
+                DrawCommand::DrawSpriteSheetCell(id, idx, xform) => {
+                    let sheets = self.sheets.borrow();
+                    let cell = sheets.get(&id).and_then(|entry| {
+                        sheets.cell(&id, idx).map(|bbox| (entry.image.as_str(), bbox))
+                    });
+
+                    match cell {
+                        Ok((image_name, bbox)) => {
+                            let atlas = match self.atlases.get(image_name) {
+                                Some(atlas) => atlas,
+                                None => self.assets.get_image(ctx, image_name),
+                            };
+                            let spec = draw_spec(&camera.apply(&xform), atlas.width() as f32, atlas.height() as f32,
+                                                 Some(bbox));
+                            (atlas, spec)
+                        },
+                        Err(e) => {
+                            println!("Skipping draw: {}", e);
+                            continue;
+                        }
+                    }
+                }
+                DrawCommand::DrawText(line, xform) => {
+                    if !self.texts.contains_key(&line) {
+                        let text = graphics::Text::new(ctx, &line, &self.font)?;
+                        self.texts.insert(line.clone(), text);
+                    }
+                    {
+                        let text = &self.texts[&line];
+                        let spec = draw_spec(&xform, text.width() as f32, text.height() as f32, None);
+                        graphics::draw_ex(ctx, text, draw_param(spec))?;
+                    }
+                    drawn_texts.insert(line);
+                    continue;
+                }
+            };
 
-                    if let Some(cell) = maybe_cell {
-                        let param = graphics::DrawParam {
-                            src: graphics::Rect::new(
-                                cell.bbox.x as f32 / w,
-                                cell.bbox.y as f32 / h,
-                                cell.bbox.width as f32 / w,
-                                cell.bbox.height as f32 / h),
-                            dest: pos,
-                            scale: scale,
-                            ..Default::default()
-                        };
-
-                        graphics::draw_ex(ctx, atlas,  param)?;
+            graphics::draw_ex(ctx, image, draw_param(spec))?;
+        }
+        self.texts.retain(|line, _| drawn_texts.contains(line));
+        graphics::present(ctx);
+        Ok(())
+    }
+}
+
+
+/// Settings picked up from the command line.
+struct Options {
+    seed: u32,
+    /// Path to write a replay of this session to.
+    record: Option<String>,
+    playback: Option<Replay>,
+    /// Run this many ticks without opening a window, then exit.
+    headless: Option<usize>,
+    /// Button presses for a headless run, eg. `500:down,4500:up`.
+    script: Option<String>,
+    /// Put on any high scores set this session.
+    initials: String,
+    /// Reload sheets and images as they're edited.
+    watch: bool,
+    controls: InputMap,
+    config: GameConfig,
+}
+
+impl Options {
+    fn from_args() -> Result<Self, String> {
+        let mut opts = Options {
+            seed: rand::thread_rng().gen(),
+            record: None,
+            playback: None,
+            headless: None,
+            script: None,
+            initials: DEFAULT_INITIALS.to_string(),
+            watch: false,
+            controls: InputMap::default(),
+            config: GameConfig::default(),
+        };
+        let mut controls_path = None;
+        let mut config_path = None;
+        let mut tick_rate = None;
+        let mut opponent = None;
+        let mut args = env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_ref() {
+                "--seed" => {
+                    match args.next().and_then(|s| s.parse().ok()) {
+                        Some(seed) => opts.seed = seed,
+                        None => println!("--seed expects a number, ignoring."),
+                    }
+                },
+                "--record" => opts.record = args.next(),
+                // playing something other than what was asked for would only hide the problem
+                "--playback" => {
+                    let path = args.next().ok_or("--playback expects the path of a replay")?;
+                    match Replay::load(&path) {
+                        Ok(replay) => opts.playback = Some(replay),
+                        Err(e) => return Err(format!("Could not load replay {}: {}", path, e)),
                     }
                 },
-                _ => ()
+                "--headless" => {
+                    match args.next().and_then(|s| s.parse().ok()) {
+                        Some(ticks) => opts.headless = Some(ticks),
+                        None => return Err("--headless expects a number of ticks".to_string()),
+                    }
+                },
+                "--script" => opts.script = args.next(),
+                "--controls" => controls_path = args.next(),
+                "--config" => config_path = args.next(),
+                "--tick-rate" => {
+                    match args.next().and_then(|s| s.parse().ok()) {
+                        Some(rate) if rate > 0 => tick_rate = Some(rate),
+                        _ => println!("--tick-rate expects a number of ticks per second, ignoring."),
+                    }
+                },
+                "--opponent" => {
+                    match args.next().as_ref().and_then(|name| Opponent::parse(name)) {
+                        Some(choice) => opponent = Some(choice),
+                        None => println!("--opponent expects `machine` or `alien`, ignoring."),
+                    }
+                },
+                "--initials" => {
+                    match args.next() {
+                        Some(initials) => opts.initials = initials,
+                        None => println!("--initials expects up to three letters, ignoring."),
+                    }
+                },
+                "--watch" => opts.watch = true,
+                _ => println!("Unknown argument: {}", arg),
             }
         }
-        graphics::present(ctx);
-        Ok(())
+
+        // a missing controls file is only worth mentioning if someone asked for it
+        let path = controls_path.clone().unwrap_or(DEFAULT_CONTROLS.to_string());
+        match InputMap::load(&path) {
+            Ok(map) => opts.controls = map,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound && controls_path.is_none() => (),
+            Err(e) => println!("Could not load controls {}, using the defaults: {}", path, e),
+        }
+
+        // unlike the controls, a broken config isn't something to play through
+        let path = config_path.clone().unwrap_or(DEFAULT_CONFIG.to_string());
+        match GameConfig::load(&path) {
+            Ok(config) => opts.config = config,
+            Err(ConfigError::Io(ref e)) if e.kind() == io::ErrorKind::NotFound && config_path.is_none() => (),
+            Err(e) => return Err(format!("Could not load config {}: {}", path, e)),
+        }
+        if let Some(rate) = tick_rate {
+            opts.config.timing.tick_rate = rate;
+        }
+        if let Some(choice) = opponent {
+            opts.config.opponent = choice;
+        }
+        Ok(opts)
     }
 }
 
 
 pub fn main() {
+    let opts = match Options::from_args() {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    if opts.playback.is_none() {
+        println!("Using seed {} (pass `--seed {}` to replay this game).", opts.seed, opts.seed);
+    }
+
+    if let Some(ticks) = opts.headless {
+        let config = match opts.playback {
+            Some(ref replay) => replay.playback_config(&opts.config),
+            None => opts.config.clone(),
+        };
+        let (seed, input) = match (opts.playback, opts.script) {
+            (Some(replay), _) => (replay.seed, headless::InputSource::Replay(replay)),
+            (None, Some(spec)) => {
+                match headless::ScriptedInput::parse(headless::DEFAULT_DELTA_MS, &spec) {
+                    Ok(script) => (opts.seed, headless::InputSource::Script(script)),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        process::exit(1);
+                    }
+                }
+            },
+            (None, None) => (opts.seed, headless::InputSource::Script(headless::ScriptedInput::default_script())),
+        };
+        match headless::run(seed, ticks, input, &config) {
+            Ok(report) => report.print(),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
 
     let mut conf = conf::Conf::new();
-    conf.window_height = 768;
-    conf.window_width = 1024;
-    conf.window_title = "Home World Derby".to_string();
+    conf.window_height = opts.config.window.height;
+    conf.window_width = opts.config.window.width;
+    conf.window_title = opts.config.window.title.clone();
 
     println!("Starting with default config: {:#?}", conf);
 
-    let ctx = &mut Context::load_from_conf("HWD", "HWD", conf).unwrap();
+    let ctx = &mut match Context::load_from_conf("HWD", "HWD", conf) {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            eprintln!("Couldn't start ggez: {}", e);
+            process::exit(1);
+        }
+    };
 
-    let state = &mut MainState::new(ctx).unwrap();
+    let state = &mut match MainState::new(ctx, opts) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("Couldn't load the game: {}", e);
+            process::exit(1);
+        }
+    };
     if let Err(e) = run(ctx, state) {
-        println!("Error encountered: {}", e);
+        eprintln!("Error encountered: {}", e);
+        process::exit(1);
     } else {
         println!("Game exited cleanly.");
     }
@@ -0,0 +1,102 @@
+//! The power meter: a level that sweeps back and forth while the pitcher winds up, and how good a
+//! swing is depending on where it was when the button came up.
+//!
+//! The level runs from 0 (empty) to 1 (full) and starts each windup empty. Letting go inside the
+//! sweet spot is a perfect swing, close to it is good, and anywhere else is weak. The grade (not
+//! the raw level) decides how hard the ball comes off the bat.
+
+use std::f32::consts::PI;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Waveform {
+    /// Eases in and out at either end.
+    Sine,
+    /// Constant speed up, then back down.
+    Triangle,
+    /// Constant speed up, then straight back to empty.
+    Sawtooth,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SwingGrade {
+    Perfect,
+    Good,
+    Weak,
+}
+
+impl SwingGrade {
+    /// How hard a swing with this grade is, from 0 to 1 (see `systems::swing_boost`).
+    pub fn strength(&self) -> f32 {
+        match *self {
+            SwingGrade::Perfect => 1.,
+            SwingGrade::Good => 0.7,
+            SwingGrade::Weak => 0.35,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match *self {
+            SwingGrade::Perfect => "Perfect timing!",
+            SwingGrade::Good => "Good timing",
+            SwingGrade::Weak => "Weak swing",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MeterModel {
+    pub waveform: Waveform,
+    /// Time (in ms) for the level to go through one full cycle. Smaller is faster (and harder).
+    pub period_ms: f32,
+    /// The level a perfect swing is aimed at.
+    pub sweet_spot: f32,
+    /// How far either side of the sweet spot still counts as perfect...
+    pub perfect_window: f32,
+    /// ...and as good.
+    pub good_window: f32,
+}
+
+impl Default for MeterModel {
+    fn default() -> Self {
+        MeterModel {
+            waveform: Waveform::Sine,
+            // about as fast as the meter always was
+            period_ms: 1570.,
+            sweet_spot: 0.85,
+            perfect_window: 0.05,
+            good_window: 0.2,
+        }
+    }
+}
+
+impl MeterModel {
+    /// The level (0 to 1) after `time_ms` of winding up.
+    pub fn level(&self, time_ms: f32) -> f32 {
+        let phase = (time_ms / self.period_ms).fract();
+        let phase = if phase < 0. { phase + 1. } else { phase };
+        match self.waveform {
+            Waveform::Sine => 0.5 - 0.5 * (2. * PI * phase).cos(),
+            Waveform::Triangle => 1. - (1. - 2. * phase).abs(),
+            Waveform::Sawtooth => phase,
+        }
+    }
+
+    pub fn grade(&self, level: f32) -> SwingGrade {
+        let off = (level - self.sweet_spot).abs();
+        if off <= self.perfect_window {
+            SwingGrade::Perfect
+        } else if off <= self.good_window {
+            SwingGrade::Good
+        } else {
+            SwingGrade::Weak
+        }
+    }
+
+    /// Where the pointer goes for `level`, from -1 (empty) to 1 (full).
+    pub fn pointer_offset(&self, level: f32) -> f32 {
+        2. * level.max(0.).min(1.) - 1.
+    }
+}
@@ -0,0 +1,114 @@
+//! What the pitching machine can throw, and how often, depending on the difficulty.
+//!
+//! A pitch is a speed, some break, and a delay between the end of the pitching animation and the
+//! ball actually leaving the machine (so a changeup looks like a fastball right up until it
+//! doesn't). Break is a turn rate: the ball's heading bends by `spin` radians every ms on its way
+//! to the plate, up to `MAX_BREAK` away from straight down, and stops bending once it's been hit.
+
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use components::Ball;
+use systems::PITCH_VELOCITY;
+
+
+/// Furthest (in radians) a pitch can bend away from straight down the screen.
+pub const MAX_BREAK: f32 = PI / 8.;
+/// Straight down the screen, toward the plate.
+pub const PITCH_ANGLE: f32 = PI / 2.;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PitchType {
+    Fastball,
+    Curveball,
+    Changeup,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PitchSpec {
+    pub kind: PitchType,
+    /// In pixels per ms.
+    pub speed: f32,
+    /// How fast the heading turns (in radians per ms). The sign says which way it breaks.
+    pub spin: f32,
+    /// How long after the pitching animation the ball is let go (in ms).
+    pub release_delay_ms: f32,
+}
+
+impl PitchSpec {
+    pub fn new(kind: PitchType, speed: f32, spin: f32, release_delay_ms: f32) -> Self {
+        PitchSpec {
+            kind: kind,
+            speed: speed,
+            spin: spin,
+            release_delay_ms: release_delay_ms,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::Normal
+    }
+}
+
+impl Difficulty {
+    /// The pitches thrown at this difficulty, with how likely each is (relative to the others).
+    pub fn pitch_mix(&self) -> Vec<(u32, PitchSpec)> {
+        match *self {
+            Difficulty::Easy => vec![
+                (7, PitchSpec::new(PitchType::Fastball, 0.35, 0., 0.)),
+                (3, PitchSpec::new(PitchType::Changeup, 0.25, 0., 200.)),
+            ],
+            Difficulty::Normal => vec![
+                (2, PitchSpec::new(PitchType::Fastball, PITCH_VELOCITY, 0., 0.)),
+                (1, PitchSpec::new(PitchType::Curveball, 0.35, 0.0015, 0.)),
+                (1, PitchSpec::new(PitchType::Changeup, 0.28, 0., 250.)),
+            ],
+            Difficulty::Hard => vec![
+                (9, PitchSpec::new(PitchType::Fastball, 0.55, 0., 0.)),
+                (7, PitchSpec::new(PitchType::Curveball, 0.45, 0.0025, 0.)),
+                (4, PitchSpec::new(PitchType::Changeup, 0.3, 0., 350.)),
+            ],
+        }
+    }
+
+    /// Pick the next pitch from the mix. Breaking balls go either way.
+    pub fn choose_pitch<R: Rng>(&self, rng: &mut R) -> PitchSpec {
+        let mix = self.pitch_mix();
+        let total: u32 = mix.iter().map(|&(weight, _)| weight).sum();
+        let mut roll = rng.gen_range(0, total);
+
+        let mut pitch = mix[0].1.clone();
+        for (weight, spec) in mix {
+            if roll < weight {
+                pitch = spec;
+                break;
+            }
+            roll -= weight;
+        }
+
+        if pitch.spin != 0. && rng.gen::<bool>() {
+            pitch.spin = -pitch.spin;
+        }
+        pitch
+    }
+}
+
+/// Bend the ball's heading for `delta_ms` worth of spin, if it hasn't been hit yet.
+pub fn apply_break(ball: &mut Ball, delta_ms: f32) {
+    if ball.struck || ball.spin == 0. {
+        return;
+    }
+    let bent = ball.angle + ball.spin * delta_ms;
+    ball.angle = bent.max(PITCH_ANGLE - MAX_BREAK).min(PITCH_ANGLE + MAX_BREAK);
+}
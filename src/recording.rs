@@ -0,0 +1,183 @@
+//! Recording and playback of the `TickData` stream.
+//!
+//! Since everything a tick needs is in `TickData`, a seed, the settings the game was played with
+//! and the list of ticks are enough to play a game back exactly as it happened. Replays are plain
+//! text so they can be attached to bug reports and eyeballed. The settings are the `GameConfig`
+//! as TOML, a `config ` in front of each line, and each tick has the state of every `Action`, in
+//! `ACTIONS` order:
+//!
+//! ```text
+//! seed 1234
+//! config difficulty = "hard"
+//! config opponent = "alien"
+//! config [timing]
+//! config windup_min_ms = 2000.0
+//! 16.667 Released Released Released Released Released Released Released
+//! 16.667 JustPressed Released JustPressed Released Released Released Released
+//! ```
+//!
+//! Actions missing from the end of a line (in replays from before they existed) are released, and
+//! replays without any settings are played back with whatever the game is running with.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use toml;
+
+use config::{ConfigError, GameConfig};
+use super::{InputState, TickData, ACTIONS};
+
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// Something wrong with one of the lines.
+    Line { line_no: usize, reason: String },
+    MissingSeed,
+    /// The recorded settings aren't a config the game would accept.
+    Settings(ConfigError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplayError::Io(ref e) => write!(f, "couldn't read replay: {}", e),
+            ReplayError::Line { line_no, ref reason } => write!(f, "replay line {}: {}", line_no, reason),
+            ReplayError::MissingSeed => write!(f, "replay has no `seed` line"),
+            ReplayError::Settings(ref e) => write!(f, "replay has bad settings: {}", e),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn description(&self) -> &str {
+        match *self {
+            ReplayError::Io(_) => "replay io error",
+            ReplayError::Line { .. } => "bad replay line",
+            ReplayError::MissingSeed => "replay missing seed",
+            ReplayError::Settings(_) => "bad replay settings",
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+fn invalid(line_no: usize, reason: &str) -> ReplayError {
+    ReplayError::Line { line_no: line_no, reason: reason.to_string() }
+}
+
+fn parse_input_state(s: &str) -> Option<InputState> {
+    match s {
+        "JustPressed" => Some(InputState::JustPressed),
+        "Pressed" => Some(InputState::Pressed),
+        "JustReleased" => Some(InputState::JustReleased),
+        "Released" => Some(InputState::Released),
+        _ => None
+    }
+}
+
+/// Writes every dispatched tick out to a replay file.
+pub struct TickRecorder {
+    out: BufWriter<File>,
+}
+
+impl TickRecorder {
+    pub fn create<P: AsRef<Path>>(path: P, seed: u32, config: &GameConfig) -> io::Result<Self> {
+        let settings = toml::to_string(config)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "seed {}", seed)?;
+        for line in settings.lines().filter(|line| !line.is_empty()) {
+            writeln!(out, "config {}", line)?;
+        }
+        Ok(TickRecorder { out: out })
+    }
+
+    pub fn record(&mut self, tick: &TickData) -> io::Result<()> {
+        write!(self.out, "{}", tick.delta_ms)?;
+        for &action in &ACTIONS {
+            write!(self.out, " {:?}", tick.input.get(action))?;
+        }
+        writeln!(self.out)
+    }
+}
+
+/// A recorded game, ready to be fed back into `ECS::tick`.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub seed: u32,
+    /// The settings it was recorded with, if it's recent enough to have them.
+    pub config: Option<GameConfig>,
+    pub ticks: VecDeque<TickData>,
+}
+
+impl Replay {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    pub fn read<R: BufRead>(reader: R) -> Result<Self, ReplayError> {
+        let mut seed = None;
+        let mut settings = vec![];
+        let mut ticks = VecDeque::new();
+
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_no = idx + 1;
+            let parts: Vec<&str> = line.split_whitespace().collect();
+
+            match (parts.first(), parts.len()) {
+                (None, _) => continue,
+                (Some(&"seed"), 2) if seed.is_none() => {
+                    seed = Some(parts[1].parse().map_err(|_| invalid(line_no, "bad seed"))?);
+                },
+                (Some(&"config"), _) if seed.is_some() && ticks.is_empty() => {
+                    settings.push(line.trim_left()["config".len()..].trim().to_string());
+                },
+                (Some(delta), n) if seed.is_some() && n >= 2 && n <= ACTIONS.len() + 1 => {
+                    let mut tick = TickData::new();
+                    tick.delta_ms = delta.parse().map_err(|_| invalid(line_no, "bad delta"))?;
+                    for (&action, state) in ACTIONS.iter().zip(&parts[1..]) {
+                        let state = parse_input_state(state).ok_or_else(|| invalid(line_no, "bad input state"))?;
+                        tick.input.set(action, state);
+                    }
+                    ticks.push_back(tick);
+                },
+                _ => return Err(invalid(line_no, "expected `seed <n>` followed by `<delta> <input>...` lines"))
+            }
+        }
+
+        let config = if settings.is_empty() {
+            None
+        } else {
+            let config = GameConfig::parse(&settings.join("\n")).map_err(ReplayError::Settings)?;
+            Some(config)
+        };
+
+        match seed {
+            Some(seed) => Ok(Replay { seed: seed, config: config, ticks: ticks }),
+            None => Err(ReplayError::MissingSeed)
+        }
+    }
+
+    /// The settings to play this back with: the ones it was recorded with (in `current`'s window,
+    /// since that's not part of the game), or `current` if it doesn't have any.
+    pub fn playback_config(&self, current: &GameConfig) -> GameConfig {
+        match self.config {
+            Some(ref recorded) => GameConfig { window: current.window.clone(), ..recorded.clone() },
+            None => current.clone(),
+        }
+    }
+
+    pub fn next_tick(&mut self) -> Option<TickData> {
+        self.ticks.pop_front()
+    }
+}
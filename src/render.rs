@@ -0,0 +1,62 @@
+//! The bits of drawing that don't need a GPU.
+//!
+//! Systems describe where things go with a `Transform`, and `draw_spec` works out the parameters
+//! ggez wants for it, so the math can be checked without opening a window.
+
+use ggez::graphics::{Point, Rect};
+
+
+/// Position, scale, rotation and pivot for something being drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub pos: Point,
+    pub scale: Point,
+    /// Rotation around `origin`, in radians.
+    pub rot: f32,
+    /// The pivot for rotation and scale, and the point placed at `pos`. Measured from the
+    /// top-left of the image (or cell), where `(0, 0)` is the top-left and `(1, 1)` the
+    /// bottom-right.
+    pub origin: Point,
+}
+
+impl Transform {
+    /// Centered on `(x, y)`, at 1:1 scale and no rotation.
+    pub fn at(x: f32, y: f32) -> Self {
+        Transform {
+            pos: Point::new(x, y),
+            scale: Point::new(1., 1.),
+            rot: 0.,
+            origin: Point::new(0.5, 0.5),
+        }
+    }
+}
+
+/// Everything ggez needs to draw an image, other than the image itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawSpec {
+    /// Region of the image to draw, normalized to the image size.
+    pub src: Rect,
+    pub dest: Point,
+    pub rotation: f32,
+    pub scale: Point,
+    /// ggez measures the pivot from the center of the drawn region (rather than the top-left like
+    /// `Transform.origin`), normalized to its size.
+    pub offset: Point,
+}
+
+/// Work out how to draw `cell` (a region of the image, in pixels) or the whole image if there's
+/// no cell.
+pub fn draw_spec(xform: &Transform, image_w: f32, image_h: f32, cell: Option<Rect>) -> DrawSpec {
+    let src = match cell {
+        Some(bbox) => Rect::new(bbox.x / image_w, bbox.y / image_h, bbox.w / image_w, bbox.h / image_h),
+        None => Rect::new(0., 0., 1., 1.),
+    };
+
+    DrawSpec {
+        src: src,
+        dest: xform.pos,
+        rotation: xform.rot,
+        scale: xform.scale,
+        offset: Point::new(xform.origin.x - 0.5, xform.origin.y - 0.5),
+    }
+}
@@ -0,0 +1,158 @@
+//! Keeping count: strikes, outs, innings, and when the game is over.
+//!
+//! `MatchRules` only ever sees the call on each pitch (`Foul`, `HomeRun`, `Hit` or `Miss`), so it
+//! has no idea about rendering or timing and can be checked against tables of calls.
+//!
+//! There are two ways to play:
+//!
+//! * `Innings` is (roughly) baseball. Three strikes is an out, a foul only counts as a strike until
+//!   there are two, and three outs ends the inning. Nobody runs the bases, so hits end the at-bat
+//!   but only home runs score.
+//! * `Derby` is the home run derby the game is named for. Anything that isn't a home run is an
+//!   out, and the round is over after so many outs.
+//!
+//! The pitching machine never misses the zone, so there are no balls (or walks) to keep track of.
+
+use super::GamePhase;
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RulesMode {
+    Innings { innings: u32 },
+    Derby { outs_per_round: u32 },
+}
+
+/// What a call did to the game, beyond the count.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RulesEvent {
+    /// Nothing other than (maybe) the count changed.
+    Pitch,
+    Strikeout,
+    /// The ball was put in play and the at-bat is over.
+    AtBatOver,
+    Out,
+    InningOver,
+    GameOver,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchRules {
+    pub mode: RulesMode,
+    pub strikes: u32,
+    pub outs: u32,
+    /// Starts at 1.
+    pub inning: u32,
+    pub runs: u32,
+    pub home_runs: u32,
+    pub game_over: bool,
+}
+
+pub const STRIKES_PER_OUT: u32 = 3;
+pub const OUTS_PER_INNING: u32 = 3;
+/// Outs in a round of the derby, unless told otherwise.
+pub const DEFAULT_DERBY_OUTS: u32 = 10;
+
+impl MatchRules {
+    pub fn new(mode: RulesMode) -> Self {
+        MatchRules {
+            mode: mode,
+            strikes: 0,
+            outs: 0,
+            inning: 1,
+            runs: 0,
+            home_runs: 0,
+            game_over: false,
+        }
+    }
+
+    pub fn derby(outs_per_round: u32) -> Self {
+        Self::new(RulesMode::Derby { outs_per_round: outs_per_round })
+    }
+
+    pub fn innings(innings: u32) -> Self {
+        Self::new(RulesMode::Innings { innings: innings })
+    }
+
+    /// Update the count for the call on a pitch. Phases that aren't calls are ignored.
+    pub fn record(&mut self, call: &GamePhase) -> RulesEvent {
+        if self.game_over {
+            return RulesEvent::GameOver;
+        }
+
+        match self.mode.clone() {
+            RulesMode::Derby { outs_per_round } => self.record_derby(call, outs_per_round),
+            RulesMode::Innings { innings } => self.record_innings(call, innings),
+        }
+    }
+
+    fn record_derby(&mut self, call: &GamePhase, outs_per_round: u32) -> RulesEvent {
+        match *call {
+            GamePhase::HomeRun => {
+                self.home_runs += 1;
+                self.runs += 1;
+                RulesEvent::AtBatOver
+            },
+            GamePhase::Foul | GamePhase::Hit | GamePhase::Miss => {
+                self.outs += 1;
+                if self.outs >= outs_per_round {
+                    self.game_over = true;
+                    RulesEvent::GameOver
+                } else {
+                    RulesEvent::Out
+                }
+            },
+            _ => RulesEvent::Pitch
+        }
+    }
+
+    fn record_innings(&mut self, call: &GamePhase, innings: u32) -> RulesEvent {
+        match *call {
+            GamePhase::Miss => {
+                self.strikes += 1;
+                if self.strikes >= STRIKES_PER_OUT {
+                    self.strikes = 0;
+                    match self.out(innings) {
+                        RulesEvent::Out => RulesEvent::Strikeout,
+                        event => event
+                    }
+                } else {
+                    RulesEvent::Pitch
+                }
+            },
+            GamePhase::Foul => {
+                // you can't strike out on a foul
+                if self.strikes < STRIKES_PER_OUT - 1 {
+                    self.strikes += 1;
+                }
+                RulesEvent::Pitch
+            },
+            GamePhase::Hit => {
+                self.strikes = 0;
+                RulesEvent::AtBatOver
+            },
+            GamePhase::HomeRun => {
+                self.strikes = 0;
+                self.home_runs += 1;
+                self.runs += 1;
+                RulesEvent::AtBatOver
+            },
+            _ => RulesEvent::Pitch
+        }
+    }
+
+    fn out(&mut self, innings: u32) -> RulesEvent {
+        self.outs += 1;
+        if self.outs < OUTS_PER_INNING {
+            return RulesEvent::Out;
+        }
+
+        self.outs = 0;
+        if self.inning >= innings {
+            self.game_over = true;
+            RulesEvent::GameOver
+        } else {
+            self.inning += 1;
+            RulesEvent::InningOver
+        }
+    }
+}
@@ -0,0 +1,518 @@
+//! Screens the game moves between: title, gameplay, results, and a pause overlay.
+//!
+//! Scenes live on a stack. Only the scene on top gets updates and input, and what it hands back
+//! decides whether a scene gets pushed on top of it, whether it gets popped, or replaced. Nothing
+//! in here touches ggez's `Context`, so the binary just forwards events and draws the
+//! `DrawCommand`s (and plays the `SoundEvent`s) that come out, and tests can drive the whole thing
+//! with made up events.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver};
+
+use ggez::GameResult;
+use ggez::graphics::Point;
+use omn_labs::sprites::{AnimationClip, PlayMode};
+
+use audio::SoundEvent;
+use highscores::HighScores;
+use recording::{Replay, TickRecorder};
+use render::Transform;
+use rules::{MatchRules, DEFAULT_DERBY_OUTS};
+use score::Score;
+use config::GameConfig;
+use sheets::SheetRegistry;
+use systems::DrawCommand;
+use super::{Action, Delta, ECS, GamePhase, InputState, TickData};
+
+
+/// What the player did, already mapped from buttons to actions (see `input::Controls`).
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneEvent {
+    Pressed(Action),
+    Released(Action),
+}
+
+/// The backdrop for scenes that aren't looking at the game (the game draws its own).
+fn background(config: &GameConfig) -> DrawCommand {
+    let (x, y) = config.layout.background;
+    DrawCommand::DrawTransformed("background.png".to_string(), Transform::at(x, y))
+}
+
+/// A screen position given for a `LAYOUT_SIZE` window, moved to fit the real one.
+fn on_screen(config: &GameConfig, x: f32, y: f32) -> Transform {
+    let (x, y) = config.window.place((x, y));
+    Transform::at(x, y)
+}
+
+pub enum Transition {
+    None,
+    Push(Box<Scene>),
+    Pop,
+    Replace(Box<Scene>),
+    /// Clear the whole stack.
+    Quit,
+}
+
+pub trait Scene {
+    fn name(&self) -> &'static str;
+    fn update(&mut self, delta_ms: Delta) -> Transition;
+    fn input(&mut self, event: SceneEvent) -> Transition;
+    fn draw(&mut self, out: &mut Vec<DrawCommand>);
+
+    /// Hand over whatever should be played since the last update.
+    fn sounds(&mut self, _out: &mut Vec<SoundEvent>) {}
+
+    /// Whether the scene underneath should still be drawn (eg. a pause menu over the game).
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+pub struct SceneStack {
+    scenes: Vec<Box<Scene>>,
+    /// Sounds collected from updates, waiting for `take_sounds`.
+    sounds: Vec<SoundEvent>,
+}
+
+impl SceneStack {
+    pub fn new(initial: Box<Scene>) -> Self {
+        SceneStack { scenes: vec![initial], sounds: vec![] }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// Names of the scenes, bottom to top.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.scenes.iter().map(|scene| scene.name()).collect()
+    }
+
+    pub fn top_name(&self) -> Option<&'static str> {
+        self.scenes.last().map(|scene| scene.name())
+    }
+
+    pub fn update(&mut self, delta_ms: Delta) {
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => {
+                let transition = scene.update(delta_ms);
+                // before the transition, so a scene being replaced still gets heard
+                scene.sounds(&mut self.sounds);
+                transition
+            },
+            None => Transition::None
+        };
+        self.apply(transition);
+    }
+
+    /// Everything the scenes wanted played since the last call.
+    pub fn take_sounds(&mut self) -> Vec<SoundEvent> {
+        self.sounds.drain(..).collect()
+    }
+
+    pub fn input(&mut self, event: SceneEvent) {
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.input(event),
+            None => Transition::None
+        };
+        self.apply(transition);
+    }
+
+    /// Draw the top scene, plus everything under it that's showing through overlays.
+    pub fn draw(&mut self) -> Vec<DrawCommand> {
+        let mut bottom = self.scenes.len();
+        while bottom > 0 {
+            bottom -= 1;
+            if !self.scenes[bottom].is_overlay() {
+                break;
+            }
+        }
+
+        let mut out = vec![];
+        for scene in self.scenes.iter_mut().skip(bottom) {
+            scene.draw(&mut out);
+        }
+        out
+    }
+
+    pub fn apply(&mut self, transition: Transition) {
+        match transition {
+            Transition::None => (),
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+            },
+            Transition::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+            },
+            Transition::Quit => self.scenes.clear(),
+        }
+    }
+}
+
+
+/// How the pitches in a round were called.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoundTally {
+    pub fouls: usize,
+    pub home_runs: usize,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl RoundTally {
+    pub fn record(&mut self, phase: &GamePhase) {
+        match *phase {
+            GamePhase::Foul => self.fouls += 1,
+            GamePhase::HomeRun => self.home_runs += 1,
+            GamePhase::Hit => self.hits += 1,
+            GamePhase::Miss => self.misses += 1,
+            _ => ()
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.fouls + self.home_runs + self.hits + self.misses
+    }
+}
+
+
+/// How many of the high scores fit on the title screen.
+const TITLE_HIGH_SCORES: usize = 5;
+
+/// Waits for the player to press the button to start a game.
+pub struct TitleScene {
+    sheets: Rc<RefCell<SheetRegistry>>,
+    config: Rc<GameConfig>,
+    seed: u32,
+    /// Handed to the next game, if we're recording it.
+    pub recorder: Option<TickRecorder>,
+    /// Shown under the title, then handed to the next game for its results screen.
+    pub high_scores: Option<HighScores>,
+    clip: Option<AnimationClip>,
+}
+
+impl TitleScene {
+    pub fn new(sheets: Rc<RefCell<SheetRegistry>>, config: Rc<GameConfig>, seed: u32) -> Self {
+        let clip = sheets.borrow().clips(config.opponent.sheet()).ok()
+            .and_then(|clips| clips.create("Ready", PlayMode::Loop));
+        TitleScene {
+            sheets: sheets,
+            config: config,
+            seed: seed,
+            recorder: None,
+            high_scores: None,
+            clip: clip,
+        }
+    }
+}
+
+impl Scene for TitleScene {
+    fn name(&self) -> &'static str {
+        "title"
+    }
+
+    fn update(&mut self, delta_ms: Delta) -> Transition {
+        if let Some(ref mut clip) = self.clip {
+            clip.update(delta_ms);
+        }
+        Transition::None
+    }
+
+    fn input(&mut self, event: SceneEvent) -> Transition {
+        match event {
+            // wait for the release so the press doesn't carry over into the game
+            SceneEvent::Released(Action::Confirm) => {
+                println!("Starting game with seed {}", self.seed);
+                let rules = MatchRules::derby(DEFAULT_DERBY_OUTS);
+                match GameplayScene::new(self.sheets.clone(), self.config.clone(), self.seed, rules) {
+                    Ok(mut game) => {
+                        game.recorder = self.recorder.take();
+                        game.high_scores = self.high_scores.take();
+                        Transition::Replace(Box::new(game))
+                    },
+                    // only a sheet reloaded since startup could do this, so stay put while it gets fixed
+                    Err(e) => {
+                        println!("Couldn't start a game: {}", e);
+                        Transition::None
+                    }
+                }
+            },
+            SceneEvent::Pressed(Action::Pause) | SceneEvent::Pressed(Action::Back) => Transition::Quit,
+            _ => Transition::None,
+        }
+    }
+
+    fn draw(&mut self, out: &mut Vec<DrawCommand>) {
+        out.push(background(&self.config));
+        if let Some(idx) = self.clip.as_ref().and_then(|clip| clip.get_cell()) {
+            let scale = 4. * self.config.opponent.scale();
+            out.push(DrawCommand::DrawSpriteSheetCell(
+                self.config.opponent.sheet().to_string(),
+                idx,
+                Transform { scale: Point::new(scale, scale), ..on_screen(&self.config, 512., 384.) }));
+        }
+        out.push(DrawCommand::DrawText("HOME WORLD DERBY".to_string(), on_screen(&self.config, 512., 150.)));
+        out.push(DrawCommand::DrawText("Press confirm to play".to_string(), on_screen(&self.config, 512., 620.)));
+
+        if let Some(ref scores) = self.high_scores {
+            let top = on_screen(&self.config, 512., 220.);
+            for (i, line) in scores.lines().into_iter().take(TITLE_HIGH_SCORES).enumerate() {
+                out.push(DrawCommand::DrawText(line, Transform::at(top.pos.x, top.pos.y + 30. * i as f32)));
+            }
+        }
+    }
+}
+
+
+/// A game (or a round of the derby) running through the `ECS`, until `MatchRules` says it's over.
+pub struct GameplayScene {
+    pub ecs: ECS,
+    render_rx: Receiver<DrawCommand>,
+    sound_rx: Receiver<SoundEvent>,
+    sheets: Rc<RefCell<SheetRegistry>>,
+    config: Rc<GameConfig>,
+    last_tick: TickData,
+    current_tick: TickData,
+    /// What the `Render` system sent for the most recent tick, kept around so we can still draw
+    /// the game under the pause menu.
+    last_frame: Vec<DrawCommand>,
+    last_phase: GamePhase,
+    pub tally: RoundTally,
+    pub recorder: Option<TickRecorder>,
+    pub high_scores: Option<HighScores>,
+    /// When set, ticks come from here instead of button events.
+    pub playback: Option<Replay>,
+}
+
+impl GameplayScene {
+    pub fn new(sheets: Rc<RefCell<SheetRegistry>>, config: Rc<GameConfig>, seed: u32, rules: MatchRules)
+               -> GameResult<Self> {
+        let (tx, rx) = channel::<DrawCommand>();
+        let (sound_tx, sound_rx) = channel::<SoundEvent>();
+        let ecs = ECS::new(seed, tx, sound_tx, &sheets.borrow(), rules, &config)?;
+        Ok(GameplayScene {
+            ecs: ecs,
+            render_rx: rx,
+            sound_rx: sound_rx,
+            sheets: sheets,
+            config: config,
+            last_tick: TickData::new(),
+            current_tick: TickData::new(),
+            last_frame: vec![],
+            last_phase: GamePhase::WaitingForPlayer,
+            tally: RoundTally::default(),
+            recorder: None,
+            high_scores: None,
+            playback: None,
+        })
+    }
+
+    /// Play back a recorded game, using the seed and settings it was recorded with.
+    pub fn from_replay(sheets: Rc<RefCell<SheetRegistry>>, config: Rc<GameConfig>, replay: Replay)
+                       -> GameResult<Self> {
+        let config = Rc::new(replay.playback_config(&config));
+        let mut game = Self::new(sheets, config, replay.seed, MatchRules::derby(DEFAULT_DERBY_OUTS))?;
+        game.playback = Some(replay);
+        Ok(game)
+    }
+
+    fn update_current_tick_data(&mut self, delta_ms: Delta) {
+        self.current_tick.delta_ms = delta_ms;
+        self.current_tick.input.decay(&self.last_tick.input);
+    }
+}
+
+impl Scene for GameplayScene {
+    fn name(&self) -> &'static str {
+        "gameplay"
+    }
+
+    fn update(&mut self, delta_ms: Delta) -> Transition {
+        let replayed = self.playback.as_mut().and_then(|replay| replay.next_tick());
+        if let Some(tick) = replayed {
+            self.current_tick = tick;
+        } else {
+            if self.playback.take().is_some() {
+                println!("Replay finished, handing control back to the player.");
+            }
+            self.update_current_tick_data(delta_ms);
+        }
+
+        let recorded = match self.recorder {
+            Some(ref mut recorder) => recorder.record(&self.current_tick),
+            None => Ok(())
+        };
+        if let Err(e) = recorded {
+            println!("Stopped recording: {}", e);
+            self.recorder = None;
+        }
+
+        self.ecs.tick(self.current_tick.clone());
+        self.last_tick = self.current_tick.clone();
+        // a frame can run several ticks, and only the last of them should end up on screen
+        let frame: Vec<DrawCommand> = self.render_rx.try_iter().collect();
+        if !frame.is_empty() {
+            self.last_frame = frame;
+        }
+
+        if let Some(phase) = self.ecs.phase() {
+            if phase != self.last_phase {
+                self.tally.record(&phase);
+                let round_over = phase == GamePhase::WaitingForPlayer && self.ecs.rules().game_over;
+                self.last_phase = phase;
+
+                if round_over {
+                    let results = ResultsScene::new(self.sheets.clone(), self.config.clone(), self.ecs.seed,
+                                                    self.tally.clone(),
+                                                    self.ecs.rules(), self.ecs.score(),
+                                                    self.high_scores.take());
+                    return Transition::Replace(Box::new(results));
+                }
+            }
+        }
+        Transition::None
+    }
+
+    fn input(&mut self, event: SceneEvent) -> Transition {
+        if self.playback.is_some() {
+            return Transition::None;
+        }
+
+        match event {
+            SceneEvent::Pressed(Action::Pause) => {
+                // whatever the buttons were doing, don't let it turn into a swing when we come back
+                self.current_tick.input.clear();
+                self.last_tick.input.clear();
+                return Transition::Push(Box::new(PauseScene { config: self.config.clone() }));
+            },
+            // guard to prevent key repeats on long holds
+            SceneEvent::Pressed(action) if self.current_tick.input.get(action) != InputState::Pressed => {
+                self.current_tick.input.set(action, InputState::JustPressed);
+            },
+            SceneEvent::Released(action) => {
+                self.current_tick.input.set(action, InputState::JustReleased);
+            },
+            _ => ()
+        }
+        Transition::None
+    }
+
+    fn draw(&mut self, out: &mut Vec<DrawCommand>) {
+        out.extend(self.last_frame.iter().cloned());
+    }
+
+    fn sounds(&mut self, out: &mut Vec<SoundEvent>) {
+        out.extend(self.sound_rx.try_iter());
+    }
+}
+
+
+/// Sits over the game until the player unpauses.
+pub struct PauseScene {
+    pub config: Rc<GameConfig>,
+}
+
+impl Scene for PauseScene {
+    fn name(&self) -> &'static str {
+        "pause"
+    }
+
+    fn update(&mut self, _delta_ms: Delta) -> Transition {
+        Transition::None
+    }
+
+    fn input(&mut self, event: SceneEvent) -> Transition {
+        match event {
+            SceneEvent::Pressed(Action::Pause) | SceneEvent::Pressed(Action::Back) => Transition::Pop,
+            _ => Transition::None
+        }
+    }
+
+    fn draw(&mut self, out: &mut Vec<DrawCommand>) {
+        out.push(DrawCommand::DrawText("PAUSED".to_string(), on_screen(&self.config, 512., 384.)));
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+
+/// How the round went. Confirm goes back to the title.
+pub struct ResultsScene {
+    sheets: Rc<RefCell<SheetRegistry>>,
+    config: Rc<GameConfig>,
+    seed: u32,
+    pub tally: RoundTally,
+    pub rules: MatchRules,
+    pub score: Score,
+    high_scores: Option<HighScores>,
+    /// Where the score landed in the high score table, if it made it.
+    pub rank: Option<usize>,
+}
+
+impl ResultsScene {
+    /// Records the score in `high_scores` (when there are any to keep).
+    pub fn new(sheets: Rc<RefCell<SheetRegistry>>, config: Rc<GameConfig>, seed: u32, tally: RoundTally, rules: MatchRules,
+               score: Score, mut high_scores: Option<HighScores>) -> Self {
+        println!("Round over: {:?}, {} runs, {} points", tally, rules.runs, score.points);
+        let rank = high_scores.as_mut().and_then(|scores| scores.record(score.points, seed));
+        ResultsScene {
+            sheets: sheets,
+            config: config,
+            seed: seed,
+            tally: tally,
+            rules: rules,
+            score: score,
+            high_scores: high_scores,
+            rank: rank,
+        }
+    }
+}
+
+impl Scene for ResultsScene {
+    fn name(&self) -> &'static str {
+        "results"
+    }
+
+    fn update(&mut self, _delta_ms: Delta) -> Transition {
+        Transition::None
+    }
+
+    fn input(&mut self, event: SceneEvent) -> Transition {
+        match event {
+            SceneEvent::Released(Action::Confirm) => {
+                // a new seed for the next game, otherwise they'd all be the same
+                let mut title = TitleScene::new(self.sheets.clone(), self.config.clone(), self.seed.wrapping_add(1));
+                title.high_scores = self.high_scores.take();
+                Transition::Replace(Box::new(title))
+            },
+            _ => Transition::None
+        }
+    }
+
+    fn draw(&mut self, out: &mut Vec<DrawCommand>) {
+        out.push(background(&self.config));
+        let banner = match self.rank {
+            Some(rank) => format!("NEW HIGH SCORE! #{}", rank + 1),
+            None => "ROUND OVER".to_string(),
+        };
+        let lines = vec![
+            banner,
+            format!("Score: {}", self.score.points),
+            format!("Home runs: {}", self.tally.home_runs),
+            format!("Hits: {}", self.tally.hits),
+            format!("Fouls: {}", self.tally.fouls),
+            format!("Misses: {}", self.tally.misses),
+            format!("Best streak: {}", self.score.best_streak),
+            "Press confirm to continue".to_string(),
+        ];
+        let top = on_screen(&self.config, 512., 200.);
+        for (i, line) in lines.into_iter().enumerate() {
+            out.push(DrawCommand::DrawText(line, Transform::at(top.pos.x, top.pos.y + 50. * i as f32)));
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Points for putting the ball in play.
+//!
+//! Hits are worth more the further they'd have gone, home runs get a bonus on top, and every couple
+//! of hits in a row bumps up a multiplier until a foul or a miss resets it.
+
+use super::GamePhase;
+
+
+/// Points per foot of projected distance.
+pub const POINTS_PER_FOOT: f32 = 0.1;
+pub const HOME_RUN_BONUS: u32 = 100;
+pub const MAX_MULTIPLIER: u32 = 4;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Score {
+    pub points: u32,
+    /// Hits (home runs included) in a row.
+    pub streak: u32,
+    pub best_streak: u32,
+    /// What the last call was worth, for showing off.
+    pub last_award: u32,
+}
+
+impl Score {
+    /// The multiplier a hit would get with the current streak.
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.streak / 2).min(MAX_MULTIPLIER)
+    }
+
+    /// Score a call. `distance` is how far (in feet) the ball would have carried, and doesn't
+    /// matter for fouls or misses.
+    pub fn award(&mut self, call: &GamePhase, distance: f32) -> u32 {
+        let base = match *call {
+            GamePhase::Hit => (distance.max(0.) * POINTS_PER_FOOT) as u32,
+            GamePhase::HomeRun => (distance.max(0.) * POINTS_PER_FOOT) as u32 + HOME_RUN_BONUS,
+            GamePhase::Foul | GamePhase::Miss => {
+                self.streak = 0;
+                self.last_award = 0;
+                return 0;
+            },
+            _ => return 0
+        };
+
+        let award = base * self.multiplier();
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        self.points += award;
+        self.last_award = award;
+        award
+    }
+}
@@ -0,0 +1,355 @@
+//! All the sprite sheets the game knows about, keyed by id.
+//!
+//! A sheet with the id `bat` is read straight from `bat.ase`. If that's missing (or can't be
+//! read), it comes from `bat.json` (the Aseprite export) instead, drawn from `bat.png` sitting
+//! next to it. Either way, adding a sprite is a matter of adding its id to `DEFAULT_SHEETS`.
+//!
+//! Sheets can be reloaded while the game runs (see `SheetRegistry::reload`). Anything that makes
+//! clips holds on to the sheet's `LiveClips`, so it picks up the new timings without restarting.
+//!
+//! Exports (including the ones built from an `.ase`) are checked before `omn_labs` gets them, since
+//! it gives up on the whole game over a bad one. Anything wrong comes back as an `AssetError`
+//! naming the file.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use ggez::graphics::Rect;
+use omn_labs::sprites::{AnimationClip, ClipStore, PlayMode, SpriteSheetData};
+use serde_json;
+
+use ase::{self, AseError, AseFile};
+use error::AssetError;
+
+
+/// The sheets loaded at startup.
+pub const DEFAULT_SHEETS: &'static [&'static str] = &[
+    "bat", "pitching-machine", "pitcher", "alien", "cap", "ship", "bar", "pointer",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SheetError {
+    UnknownSheet(String),
+    UnknownCell { sheet: String, idx: usize, count: usize },
+}
+
+impl fmt::Display for SheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SheetError::UnknownSheet(ref id) => write!(f, "no sprite sheet loaded with id `{}`", id),
+            SheetError::UnknownCell { ref sheet, idx, count } =>
+                write!(f, "sprite sheet `{}` has {} cells, no cell {}", sheet, count, idx),
+        }
+    }
+}
+
+impl Error for SheetError {
+    fn description(&self) -> &str {
+        match *self {
+            SheetError::UnknownSheet(_) => "unknown sprite sheet",
+            SheetError::UnknownCell { .. } => "unknown sprite sheet cell",
+        }
+    }
+}
+
+/// A sheet's `ClipStore`, shared between the registry and everything making clips from it.
+#[derive(Clone, Debug)]
+pub struct LiveClips(Arc<RwLock<ClipStore>>);
+
+impl LiveClips {
+    pub fn new(clips: ClipStore) -> Self {
+        LiveClips(Arc::new(RwLock::new(clips)))
+    }
+
+    pub fn create(&self, name: &str, mode: PlayMode) -> Option<AnimationClip> {
+        self.0.read().unwrap().create(name, mode)
+    }
+
+    /// Swap in a freshly loaded store. Clips already playing carry on as they were.
+    pub fn replace(&self, clips: ClipStore) {
+        *self.0.write().unwrap() = clips;
+    }
+}
+
+pub struct SheetEntry {
+    pub data: SpriteSheetData,
+    pub clips: LiveClips,
+    /// Name of the image the cells are cut from: a file for the `AssetBundle` to load, unless
+    /// `pixels` are here already.
+    pub image: String,
+    /// The image, for sheets read from an `.ase`.
+    pub pixels: Option<ase::Image>,
+    /// The file the sheet was read from.
+    pub source: String,
+}
+
+fn io_error(path: &str, e: io::Error) -> AssetError {
+    match e.kind() {
+        io::ErrorKind::NotFound => AssetError::Missing(path.to_string()),
+        _ => AssetError::Unreadable { path: path.to_string(), reason: e.to_string() },
+    }
+}
+
+fn malformed(path: &str, reason: String) -> AssetError {
+    AssetError::MalformedSheet { path: path.to_string(), reason: reason }
+}
+
+/// Read `path` and build its `SpriteSheetData`, with the flattened frames as the image. The
+/// export is checked the same as one read from a `.json`.
+pub fn load_ase(path: &str, image: &str) -> Result<(SpriteSheetData, ase::Image), AssetError> {
+    let (pixels, json) = match AseFile::load(path) {
+        Ok(file) => file.export(image),
+        Err(AseError::Io(e)) => return Err(io_error(path, e)),
+        Err(e) => return Err(AssetError::Unreadable { path: path.to_string(), reason: e.to_string() }),
+    };
+    let export: ExportCheck = serde_json::from_str(&json).map_err(|e| malformed(path, e.to_string()))?;
+    check(path, &export)?;
+    Ok((SpriteSheetData::from_str(&json), pixels))
+}
+
+/// The parts of an Aseprite export the game relies on.
+#[derive(Deserialize)]
+struct ExportCheck {
+    frames: Vec<FrameCheck>,
+    meta: MetaCheck,
+}
+
+#[derive(Deserialize)]
+struct FrameCheck {
+    frame: RectCheck,
+    duration: u32,
+}
+
+#[derive(Deserialize)]
+struct RectCheck {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct SizeCheck {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MetaCheck {
+    size: SizeCheck,
+    frame_tags: Vec<TagCheck>,
+}
+
+#[derive(Deserialize)]
+struct TagCheck {
+    name: String,
+    from: usize,
+    to: usize,
+    direction: String,
+}
+
+/// Make sure the export at `path` is one `omn_labs` can read.
+pub fn check_export(path: &str) -> Result<(), AssetError> {
+    let file = File::open(path).map_err(|e| io_error(path, e))?;
+    let export: ExportCheck = serde_json::from_reader(file).map_err(|e| malformed(path, e.to_string()))?;
+    check(path, &export)
+}
+
+/// Problems are reported against `path`, whether the export came from there or was built from it.
+fn check(path: &str, export: &ExportCheck) -> Result<(), AssetError> {
+    let size = &export.meta.size;
+    for (idx, frame) in export.frames.iter().enumerate() {
+        let rect = &frame.frame;
+        if rect.x + rect.w > size.w || rect.y + rect.h > size.h {
+            return Err(malformed(path, format!("frame {} runs off the {}x{} image", idx, size.w, size.h)));
+        }
+        if frame.duration == 0 {
+            return Err(malformed(path, format!("frame {} has no duration", idx)));
+        }
+    }
+
+    let count = export.frames.len();
+    for tag in &export.meta.frame_tags {
+        if tag.from > tag.to || tag.to >= count {
+            return Err(malformed(path, format!("tag `{}` covers frames {} to {}, but there are {} frames",
+                                               tag.name, tag.from, tag.to, count)));
+        }
+        match tag.direction.as_ref() {
+            "forward" | "reverse" | "pingpong" => (),
+            other => return Err(malformed(path, format!("tag `{}` plays `{}`, which isn't a direction",
+                                                        tag.name, other))),
+        }
+    }
+    Ok(())
+}
+
+/// Read the export `<dir>/<id>.json`, making sure `<id>.png` is there for it to draw from.
+fn read_export(dir: &str, id: &str) -> Result<SpriteSheetData, AssetError> {
+    let path = format!("{}/{}.json", dir, id);
+    check_export(&path)?;
+    let image = format!("{}/{}.png", dir, id);
+    if !Path::new(&image).is_file() {
+        return Err(AssetError::Missing(image));
+    }
+    Ok(SpriteSheetData::from_file(&path))
+}
+
+impl SheetEntry {
+    fn new(data: SpriteSheetData, image: &str, pixels: Option<ase::Image>, source: &str) -> Self {
+        SheetEntry {
+            clips: LiveClips::new(data.clips.clone()),
+            data: data,
+            image: image.to_string(),
+            pixels: pixels,
+            source: source.to_string(),
+        }
+    }
+}
+
+pub struct SheetRegistry {
+    sheets: HashMap<String, SheetEntry>,
+}
+
+impl SheetRegistry {
+    pub fn new() -> Self {
+        SheetRegistry { sheets: HashMap::new() }
+    }
+
+    /// Load `<id>.ase` for each id from `dir`, or `<id>.json` paired with `<id>.png` when there's
+    /// no `.ase` to be had.
+    pub fn load(dir: &str, ids: &[&str]) -> Result<Self, AssetError> {
+        let mut registry = Self::new();
+        for id in ids {
+            let path = format!("{}/{}.ase", dir, id);
+            let image = format!("{}.ase", id);
+            match load_ase(&path, &image) {
+                Ok((data, pixels)) => {
+                    registry.sheets.insert(id.to_string(), SheetEntry::new(data, &image, Some(pixels), &path));
+                    continue;
+                },
+                Err(AssetError::Missing(_)) => (),
+                Err(e) => println!("Couldn't use {}, trying the export instead: {}", path, e),
+            }
+            registry.load_export(dir, id)?;
+        }
+        Ok(registry)
+    }
+
+    /// Load `<id>.json` from `dir`, pairing it with `<id>.png`.
+    pub fn load_export(&mut self, dir: &str, id: &str) -> Result<(), AssetError> {
+        let data = read_export(dir, id)?;
+        self.insert(id, data, &format!("{}.png", id), &format!("{}/{}.json", dir, id));
+        Ok(())
+    }
+
+    pub fn insert(&mut self, id: &str, data: SpriteSheetData, image: &str, source: &str) {
+        self.sheets.insert(id.to_string(), SheetEntry::new(data, image, None, source));
+    }
+
+    /// Read the sheet `file` belongs to again from `dir`, and hand its clips to everyone holding
+    /// its `LiveClips`. An edited `.ase` is read as an `.ase`; an edited `.json` or `.png` brings
+    /// in the export instead, whichever the sheet came from before. If that fails the sheet stays
+    /// as it was.
+    pub fn reload(&mut self, dir: &str, file: &str) -> Result<(), AssetError> {
+        let id = match self.sheet_for(file) {
+            Some(id) => id.to_string(),
+            None => return Err(SheetError::UnknownSheet(file.to_string()).into()),
+        };
+        let (data, image, pixels, source) = if file.ends_with(".ase") {
+            let path = format!("{}/{}", dir, file);
+            let (data, pixels) = load_ase(&path, file)?;
+            (data, file.to_string(), Some(pixels), path)
+        } else {
+            (read_export(dir, &id)?, format!("{}.png", id), None, format!("{}/{}.json", dir, id))
+        };
+
+        let entry = self.sheets.get_mut(&id).unwrap();
+        entry.clips.replace(data.clips.clone());
+        entry.data = data;
+        entry.image = image;
+        entry.pixels = pixels;
+        entry.source = source;
+        Ok(())
+    }
+
+    /// The id of the sheet `file` (a name in the sheets' directory) is one of the sources of:
+    /// its `.ase`, or its exported `.json` and `.png`.
+    pub fn sheet_for(&self, file: &str) -> Option<&str> {
+        let path = Path::new(file);
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ase") | Some("json") | Some("png") => (),
+            _ => return None,
+        }
+        let stem = path.file_stem().and_then(|stem| stem.to_str());
+        self.sheets.keys()
+            .find(|id| Some(id.as_str()) == stem)
+            .map(|id| id.as_ref())
+    }
+
+    pub fn get(&self, id: &str) -> Result<&SheetEntry, SheetError> {
+        self.sheets.get(id).ok_or_else(|| SheetError::UnknownSheet(id.to_string()))
+    }
+
+    pub fn clips(&self, id: &str) -> Result<&ClipStore, SheetError> {
+        self.get(id).map(|entry| &entry.data.clips)
+    }
+
+    /// The sheet's clips, kept up to date across reloads.
+    pub fn live_clips(&self, id: &str) -> Result<LiveClips, SheetError> {
+        self.get(id).map(|entry| entry.clips.clone())
+    }
+
+    /// The sheet's clips, as long as it has an animation for every one of `tags`.
+    pub fn require_clips(&self, id: &str, tags: &[&str]) -> Result<LiveClips, AssetError> {
+        let entry = self.get(id)?;
+        for tag in tags {
+            if entry.clips.create(tag, PlayMode::Loop).is_none() {
+                return Err(AssetError::MissingClip { path: entry.source.clone(), tag: tag.to_string() });
+            }
+        }
+        Ok(entry.clips.clone())
+    }
+
+    /// The region (in pixels) of the sheet's image that cell `idx` occupies.
+    pub fn cell(&self, id: &str, idx: usize) -> Result<Rect, SheetError> {
+        let entry = self.get(id)?;
+        match entry.data.cells.get(idx) {
+            Some(cell) => Ok(Rect::new(
+                cell.bbox.x as f32,
+                cell.bbox.y as f32,
+                cell.bbox.width as f32,
+                cell.bbox.height as f32)),
+            None => Err(SheetError::UnknownCell {
+                sheet: id.to_string(),
+                idx: idx,
+                count: entry.data.cells.len(),
+            })
+        }
+    }
+
+    /// Every image the loaded sheets draw from that needs loading from disk, for handing to the
+    /// `AssetBundle`.
+    pub fn images(&self) -> Vec<&str> {
+        let mut images: Vec<&str> = self.sheets.values()
+            .filter(|entry| entry.pixels.is_none())
+            .map(|entry| entry.image.as_ref())
+            .collect();
+        images.sort();
+        images
+    }
+
+    /// The images that came with their sheets, by name.
+    pub fn pixels(&self) -> Vec<(&str, &ase::Image)> {
+        let mut pixels: Vec<(&str, &ase::Image)> = self.sheets.values()
+            .filter_map(|entry| entry.pixels.as_ref().map(|pixels| (entry.image.as_ref(), pixels)))
+            .collect();
+        pixels.sort_by_key(|&(name, _)| name);
+        pixels
+    }
+}
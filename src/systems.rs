@@ -9,34 +9,111 @@
 //! * if bat and ball collide, then we do a reflection calc for the angle and pump up the
 //!   velocity on the ball
 //! * largeish bbox for bounds checking - when ball exits pitcher is ready (again)
-//! *
+//! * once the ball is out of play the umpire makes the call (foul, home run, hit, miss), updates
+//!   the count in `MatchRules`, and leaves it up for a bit before resetting the at-bat
 //!
 
+use std::f32::consts::PI;
 use std::sync::mpsc::Sender;
 
 use ggez::graphics;
 use specs;
 use specs::Join;
-use rand::{self, Rng};
+use rand::{Rng, XorShiftRng};
 
-use omn_labs::sprites::{ClipStore, PlayMode};
+use omn_labs::sprites::PlayMode;
+use audio::SoundEvent;
+use camera::Camera;
 use components::*;
-use super::{InputState, TickData, GamePhase};
+use config::{GameConfig, LayoutConfig};
+use meter::SwingGrade;
+use pitches::{apply_break, PITCH_ANGLE};
+use render::Transform;
+use rules::{MatchRules, RulesMode};
+use score::Score;
+use sheets::LiveClips;
+use super::{Action, InputState, TickData, GamePhase};
 
+#[derive(Clone, Debug)]
 pub enum DrawCommand {
-    DrawTransformed {
-        path: String,
-        x: f32,
-        y: f32,
-        rot: f32,
-        sx: f32,
-        sy: f32,
-    },
-    DrawSpriteSheetCell(String, usize, graphics::Point, graphics::Point),
+    /// Draw a whole image.
+    DrawTransformed(String, Transform),
+    /// Draw a single cell (by index) of the sprite sheet with the given id.
+    DrawSpriteSheetCell(String, usize, Transform),
+    /// Draw a line of text in the HUD font. Always in screen space.
+    DrawText(String, Transform),
+    /// Look through this camera for the sprites and images that follow (until the next one).
+    /// Each frame starts out looking at the plate.
+    Camera(Camera),
 }
 
 
 
+/// Speed of a fastball on normal difficulty (in pixels per ms).
+pub const PITCH_VELOCITY: f32 = 0.4;
+/// How much faster than a `PITCH_VELOCITY` pitch the ball comes off the bat, swung as hard as it goes.
+pub const BAT_BOOST: f32 = 2.5;
+/// The bat faces out toward the field (up the screen).
+pub const BAT_NORMAL: f32 = -PI / 2.;
+/// Once the ball leaves this area it is considered out of play. The field goes well past the
+/// screen in every direction but back behind the plate.
+pub const FIELD_BOUNDS: (f32, f32, f32, f32) = (-1024., -2304., 3072., 3072.);
+/// How far (in radians) either side of straight away from the plate a hit can go and stay fair.
+pub const FAIR_ARC: f32 = PI / 4.;
+/// Fair balls leaving the bat at least this fast (in pixels per ms) are gone.
+pub const HOME_RUN_VELOCITY: f32 = 0.9;
+/// Fraction of the full `BAT_BOOST` a swing gets even with the meter at zero.
+pub const MIN_SWING_STRENGTH: f32 = 0.4;
+/// The batter is drawn at half size.
+pub const BATTER_SCALE: f32 = 0.5;
+/// Where the batter holds the bat, relative to the bat's cell (see `Transform.origin`).
+pub const BAT_HANDLE: (f32, f32) = (0.25, 0.75);
+/// The part of the bat that can hit the ball, as `(x, y, w, h)` from the handle (`layout.bat`).
+pub const BAT_HITBOX: (f32, f32, f32, f32) = (-48., -52., 160., 40.);
+/// Cells of the "Swing" clip where the bat is actually through the zone.
+pub const SWING_ACTIVE_CELLS: (usize, usize) = (1, 2);
+/// How far (in radians) the bat turns about the handle over a whole swing, toward the pitcher.
+pub const BAT_SWING_ARC: f32 = PI / 3.;
+/// How far (in feet) a ball leaving the bat at 1 pixel per ms would carry.
+pub const FEET_PER_VELOCITY: f32 = 400.;
+
+/// Tags of the bat's animations `BatterThink` plays. `ECS::new` makes sure they're all there.
+pub const BAT_CLIPS: &'static [&'static str] = &["Idle", "Swing"];
+/// Tags of the opponent's animations `PitcherThink` plays.
+pub const PITCHER_CLIPS: &'static [&'static str] = &["Ready", "Winding", "Pitching", "Not Ready"];
+/// Tags of the power meter's animations `PowerMeterSys` plays.
+pub const METER_CLIPS: &'static [&'static str] = &["No Bar", "Bar"];
+/// Tags of the power meter's pointer animation.
+pub const POINTER_CLIPS: &'static [&'static str] = &["Default"];
+
+
+/// Move the ball along (and bend) its heading for `delta_ms`.
+pub fn advance_ball(ball: &mut Ball, delta_ms: f32) {
+    apply_break(ball, delta_ms);
+    ball.pos.x += ball.angle.cos() * ball.velocity * delta_ms;
+    ball.pos.y += ball.angle.sin() * ball.velocity * delta_ms;
+    ball.rotation += ball.velocity * delta_ms / (BALL_SIZE / 2.);
+    ball.sync_bbox();
+}
+
+/// Where the bat can hit the ball, with its handle where `layout` puts it.
+pub fn bat_bbox(layout: &LayoutConfig) -> graphics::Rect {
+    let (x, y, w, h) = BAT_HITBOX;
+    let (hx, hy) = layout.bat;
+    graphics::Rect::new(hx + x, hy + y, w, h)
+}
+
+/// How far the bat is turned about its handle: not at all at rest, and through `BAT_SWING_ARC`
+/// (counterclockwise, so up the screen) as the "Swing" clip plays out.
+pub fn bat_rotation(bat: &Bat) -> f32 {
+    match bat.active_clip {
+        Some(ref clip) if clip.name == "Swing" && clip.duration > 0. => {
+            -BAT_SWING_ARC * (bat.swing_ms / clip.duration).max(0.).min(1.)
+        },
+        _ => 0.
+    }
+}
+
 fn key_pressed(input: &InputState) -> bool {
     match *input {
         InputState::Pressed | InputState::JustReleased => true,
@@ -44,14 +121,74 @@ fn key_pressed(input: &InputState) -> bool {
     }
 }
 
+pub fn overlaps(a: &graphics::Rect, b: &graphics::Rect) -> bool {
+    a.x < b.x + b.w && b.x < a.x + a.w && a.y < b.y + b.h && b.y < a.y + a.h
+}
+
+/// Bring an angle into the range `-PI..PI`.
+pub fn wrap_angle(angle: f32) -> f32 {
+    let turns = ((angle + PI) / (2. * PI)).floor();
+    angle - turns * 2. * PI
+}
+
+/// Bounce a heading off a surface facing `normal`, giving an angle in the range `-PI..PI`.
+pub fn reflect(angle: f32, normal: f32) -> f32 {
+    wrap_angle(2. * normal - angle + PI)
+}
+
+/// How far (in feet) a struck ball would carry, going by how hard and how square it was hit.
+pub fn projected_distance(ball: &Ball) -> f32 {
+    if !ball.struck {
+        return 0.;
+    }
+    let off_center = wrap_angle(ball.angle - BAT_NORMAL).abs();
+    ball.velocity * FEET_PER_VELOCITY * off_center.cos().max(0.)
+}
+
+/// Make the call on a pitch, or `None` if the ball is still in play.
+pub fn judge_pitch(ball: &Ball) -> Option<GamePhase> {
+    if !ball.out_of_bounds {
+        return None;
+    }
+
+    if !ball.struck {
+        Some(GamePhase::Miss)
+    } else if wrap_angle(ball.angle - BAT_NORMAL).abs() > FAIR_ARC {
+        Some(GamePhase::Foul)
+    } else if ball.velocity >= HOME_RUN_VELOCITY {
+        Some(GamePhase::HomeRun)
+    } else {
+        Some(GamePhase::Hit)
+    }
+}
+
+/// Velocity multiplier for a swing of the given power (0 to 1).
+pub fn swing_boost(power: f32) -> f32 {
+    BAT_BOOST * (MIN_SWING_STRENGTH + (1. - MIN_SWING_STRENGTH) * power.max(0.).min(1.))
+}
+
+/// How fast (in pixels per ms) the ball comes off the bat for a swing of the given power. It's
+/// boosted from a `PITCH_VELOCITY` pitch whatever was actually thrown, so a perfect swing can go
+/// deep on every difficulty; faster pitches are just harder to time.
+pub fn exit_velocity(power: f32) -> f32 {
+    PITCH_VELOCITY * swing_boost(power)
+}
+
+fn in_bounds(pos: &graphics::Point) -> bool {
+    let (x, y, w, h) = FIELD_BOUNDS;
+    pos.x >= x && pos.x <= x + w && pos.y >= y && pos.y <= y + h
+}
+
 #[derive(Clone, Debug)]
 pub struct PowerMeterSys {
-    pub clips: ClipStore,
+    pub clips: LiveClips,
 }
 
 impl specs::System<TickData> for PowerMeterSys {
     fn run(&mut self, arg: specs::RunArg, data: TickData) {
-        let (game_flow, mut power_meter) = arg.fetch(|w| { (w.read::<GameFlow>(), w.write::<PowerMeter>()) });
+        let (game_flow, mut power_meter, model) = arg.fetch(|w| {
+            (w.read::<GameFlow>(), w.write::<PowerMeter>(), w.read_resource::<GameConfig>().meter.clone())
+        });
         for (flow, meter) in (&game_flow, &mut power_meter).iter() {
             let mut idle = false;
             if let Some(ref clip) = meter.active_clip {
@@ -61,12 +198,13 @@ impl specs::System<TickData> for PowerMeterSys {
             match (*flow).active {
                 GamePhase::WaitingForPlayer => {
                     if !idle {
-                        meter.active_clip = Some(self.clips.create("No Bar", PlayMode::Loop).unwrap());
+                        meter.active_clip = self.clips.create("No Bar", PlayMode::Loop);
                     }
                 },
-                GamePhase::Windup => {
+                // keeps going until the pitch is called, since the swing can come any time before then
+                GamePhase::Windup | GamePhase::Pitching | GamePhase::BallInFlight => {
                     meter.time += data.delta_ms;
-                    meter.power_level = (meter.time / 250.).sin();
+                    meter.power_level = model.level(meter.time);
 
 
                     // FIXME: could skip looking at clip names if flow or TickData had a value for `prev_phase`
@@ -74,7 +212,7 @@ impl specs::System<TickData> for PowerMeterSys {
                         idle = clip.name == "No Bar";
                     }
                     if idle {
-                        meter.active_clip = Some(self.clips.create("Bar", PlayMode::Loop).unwrap());
+                        meter.active_clip = self.clips.create("Bar", PlayMode::Loop);
                     }
 
 
@@ -94,24 +232,49 @@ impl specs::System<TickData> for PowerMeterSys {
 
 #[derive(Clone, Debug)]
 pub struct BatterThink {
-    pub clips: ClipStore
+    pub clips: LiveClips
 }
 
 impl specs::System<TickData> for BatterThink {
     fn run(&mut self, arg: specs::RunArg, data: TickData) {
 
-        let mut game_flow = arg.fetch(|w| { w.write::<GameFlow>() });
+        let (power_meter, mut bats, mut game_flow, model) = arg.fetch(|w| {
+            (w.read::<PowerMeter>(), w.write::<Bat>(), w.write::<GameFlow>(),
+             w.read_resource::<GameConfig>().meter.clone())
+        });
+
+        for (meter, bat, flow) in (&power_meter, &mut bats, &mut game_flow).iter() {
+            let mut idle = false;
+            if let Some(ref clip) = bat.active_clip {
+                idle = clip.name == "Idle";
+            }
 
-        for flow in (&mut game_flow).iter() {
             let maybe_phase = match (*flow).active {
                 GamePhase::WaitingForPlayer => {
-                    if key_pressed(&data.input_state) {
+                    if !idle {
+                        bat.active_clip = self.clips.create("Idle", PlayMode::Loop);
+                        bat.power = 0.;
+                        bat.grade = None;
+                    }
+
+                    if key_pressed(&data.input.get(Action::Swing)) {
                         println!("Batter Up!");
                         Some(GamePhase::PlayerReady)
                     } else {
                         Some(GamePhase::WaitingForPlayer)
                     }
                 },
+                GamePhase::Windup | GamePhase::Pitching | GamePhase::BallInFlight => {
+                    // only one swing per pitch
+                    if idle && data.input.get(Action::Swing) == InputState::JustReleased {
+                        let grade = model.grade(meter.power_level);
+                        bat.power = grade.strength();
+                        bat.grade = Some(grade);
+                        bat.swing_ms = 0.;
+                        bat.active_clip = self.clips.create("Swing", PlayMode::OneShot);
+                    }
+                    None
+                },
 
                 _ => None
             };
@@ -119,43 +282,60 @@ impl specs::System<TickData> for BatterThink {
             if let Some(phase) = maybe_phase {
                 flow.active = phase;
             }
+
+            if let Some(ref mut clip) = bat.active_clip {
+                clip.update(data.delta_ms);
+            }
+            bat.swing_ms += data.delta_ms;
+
+            bat.swinging = match bat.active_clip {
+                Some(ref clip) if clip.name == "Swing" && !clip.drained => {
+                    let (first, last) = SWING_ACTIVE_CELLS;
+                    clip.get_cell().map_or(false, |idx| idx >= first && idx <= last)
+                },
+                _ => false
+            };
         }
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct PitcherThink {
-    pub clips: ClipStore
+    pub clips: LiveClips,
+    pub sound_tx: Sender<SoundEvent>,
 }
 
 impl specs::System<TickData> for PitcherThink {
     fn run(&mut self, arg: specs::RunArg, data: TickData) {
 
-        let (batter, mut game_flow, mut pitcher) = arg.fetch(|w| {
-            (w.read::<Batter>(), w.write::<GameFlow>(), w.write::<Pitcher>())
+        let (entities, batter, mut game_flow, mut pitcher, mut balls, mut rng, config) = arg.fetch(|w| {
+            (w.entities(), w.read::<Batter>(), w.write::<GameFlow>(), w.write::<Pitcher>(), w.write::<Ball>(),
+             w.write_resource::<XorShiftRng>(), w.read_resource::<GameConfig>().clone())
         });
 
-        for (flow, pitch, bat) in (&mut game_flow, &mut pitcher, &batter).iter() {
+        for (entity, flow, pitch, _) in (&entities, &mut game_flow, &mut pitcher, &batter).iter() {
             let drained = {
                 if let Some(ref clip) = pitch.active_clip { clip.drained } else { true }
             };
 
-            let mut rng = rand::thread_rng();
-
             let maybe_phase = match (*flow).active {
                 GamePhase::PlayerReady => {
                     println!("Pitch system wants to pitch!");
-                    pitch.active_clip = Some(self.clips.create("Winding", PlayMode::Loop).unwrap());
-                    pitch.action_ttl = 3000. + (rng.gen::<f32>() * 2500.);
-                    println!("Pitcher is winding up for {}!", pitch.action_ttl);
+                    pitch.active_clip = self.clips.create("Winding", PlayMode::Loop);
+                    pitch.action_ttl = config.timing.windup_min_ms + (rng.gen::<f32>() * config.timing.windup_range_ms);
+                    let spec = config.difficulty.choose_pitch(&mut *rng);
+                    println!("Pitcher is winding up for {}! ({:?})", pitch.action_ttl, spec.kind);
+                    pitch.pitch = Some(spec);
+                    self.sound_tx.send(SoundEvent::WindupHum).unwrap();
                     Some(GamePhase::Windup)
                 },
                 GamePhase::Windup => {
                     pitch.action_ttl -= data.delta_ms;
                     if pitch.action_ttl < 0. {
-                        let clip = self.clips.create("Pitching", PlayMode::OneShot).unwrap();
-                        let duration = clip.duration;
-                        pitch.active_clip = Some(clip);
+                        pitch.active_clip = self.clips.create("Pitching", PlayMode::OneShot);
+                        let duration = pitch.active_clip.as_ref().map_or(0., |clip| clip.duration);
+                        // counts down once the animation is done
+                        pitch.action_ttl = pitch.pitch.as_ref().map_or(0., |spec| spec.release_delay_ms);
                         println!("Pitcher is pitching for {}!", duration);
                         Some(GamePhase::Pitching)
                     } else {
@@ -163,7 +343,18 @@ impl specs::System<TickData> for PitcherThink {
                     }
                 },
                 GamePhase::Pitching if drained => {
-                    Some(GamePhase::BallInFlight)
+                    pitch.action_ttl -= data.delta_ms;
+                    if pitch.action_ttl <= 0. {
+                        let (x, y) = config.layout.release_point;
+                        let (speed, spin) = pitch.pitch.as_ref().map_or((PITCH_VELOCITY, 0.), |spec| (spec.speed, spec.spin));
+                        let mut ball = Ball::new(graphics::Point::new(x, y), PITCH_ANGLE, speed);
+                        ball.spin = spin;
+                        balls.insert(entity, ball);
+                        self.sound_tx.send(SoundEvent::PitchLaunch).unwrap();
+                        Some(GamePhase::BallInFlight)
+                    } else {
+                        None
+                    }
                 },
                 GamePhase::BallInFlight => {
                     let mut already_idle = false;
@@ -172,20 +363,20 @@ impl specs::System<TickData> for PitcherThink {
                     }
 
                     if !already_idle {
-                        pitch.action_ttl = 5000.;
-                        pitch.active_clip = Some(self.clips.create("Not Ready", PlayMode::Loop).unwrap())
-                    } else {
-                        pitch.action_ttl -= data.delta_ms;
+                        pitch.active_clip = self.clips.create("Not Ready", PlayMode::Loop)
                     }
-
-                    if pitch.action_ttl < 0. {
-                        // FIXME: just a temp game state reset until we have the player side implemented
-                        pitch.active_clip = Some(self.clips.create("Ready", PlayMode::Loop).unwrap());
-                        Some(GamePhase::WaitingForPlayer)
-                    } else {
-                        Some(GamePhase::BallInFlight)
+                    None
+                },
+                GamePhase::WaitingForPlayer => {
+                    let mut already_ready = false;
+                    if let Some(ref clip) = pitch.active_clip {
+                        already_ready = clip.name == "Ready";
                     }
 
+                    if !already_ready {
+                        pitch.active_clip = self.clips.create("Ready", PlayMode::Loop);
+                    }
+                    None
                 },
                 _ => None
 
@@ -204,6 +395,108 @@ impl specs::System<TickData> for PitcherThink {
 }
 
 
+/// Calls each pitch once the ball is out of play, then resets the at-bat after
+/// `timing.call_hold_ms`. The crowd goes wild for home runs.
+#[derive(Clone, Debug)]
+pub struct UmpireSys {
+    pub sound_tx: Sender<SoundEvent>,
+}
+
+impl specs::System<TickData> for UmpireSys {
+    fn run(&mut self, arg: specs::RunArg, data: TickData) {
+        let (entities, mut game_flow, mut umpires, mut balls, mut rules, mut score, timing) = arg.fetch(|w| {
+            (w.entities(), w.write::<GameFlow>(), w.write::<Umpire>(), w.write::<Ball>(),
+             w.write_resource::<MatchRules>(), w.write_resource::<Score>(),
+             w.read_resource::<GameConfig>().timing.clone())
+        });
+
+        for (entity, flow, ump) in (&entities, &mut game_flow, &mut umpires).iter() {
+            let maybe_phase = match (*flow).active {
+                GamePhase::BallInFlight => {
+                    let call = balls.get(entity).and_then(judge_pitch);
+                    if let Some(ref phase) = call {
+                        let distance = balls.get(entity).map_or(0., projected_distance);
+                        let points = score.award(phase, distance);
+                        println!("{} ft, {} points", distance as u32, points);
+
+                        let event = rules.record(phase);
+                        println!("Umpire calls it: {:?} ({:?}, {} strikes, {} outs)",
+                                 phase, event, rules.strikes, rules.outs);
+                        ump.call_ttl = timing.call_hold_ms;
+                        if *phase == GamePhase::HomeRun {
+                            self.sound_tx.send(SoundEvent::CrowdCheer).unwrap();
+                        }
+                    }
+                    call
+                },
+                GamePhase::Foul | GamePhase::HomeRun | GamePhase::Hit | GamePhase::Miss => {
+                    ump.call_ttl -= data.delta_ms;
+                    if ump.call_ttl < 0. {
+                        balls.remove(entity);
+                        Some(GamePhase::WaitingForPlayer)
+                    } else {
+                        None
+                    }
+                },
+                _ => None
+            };
+
+            if let Some(phase) = maybe_phase {
+                flow.active = phase;
+            }
+        }
+    }
+}
+
+
+/// Follows the ball once it's been hit, then heads back to the plate.
+#[derive(Clone, Debug)]
+pub struct CameraSys;
+
+impl specs::System<TickData> for CameraSys {
+    fn run(&mut self, arg: specs::RunArg, data: TickData) {
+        let (balls, mut camera) = arg.fetch(|w| (w.read::<Ball>(), w.write_resource::<Camera>()));
+
+        let target = camera.target((&balls).iter().next());
+        camera.ease_toward(&target, data.delta_ms);
+    }
+}
+
+
+/// Moves the ball along its heading and bounces it off the bat.
+#[derive(Clone, Debug)]
+pub struct BallPhysics {
+    pub sound_tx: Sender<SoundEvent>,
+}
+
+impl specs::System<TickData> for BallPhysics {
+    fn run(&mut self, arg: specs::RunArg, data: TickData) {
+        let (bats, mut balls) = arg.fetch(|w| { (w.read::<Bat>(), w.write::<Ball>()) });
+
+        for (bat, ball) in (&bats, &mut balls).iter() {
+            if ball.out_of_bounds {
+                continue;
+            }
+
+            advance_ball(ball, data.delta_ms);
+
+            if bat.swinging && !ball.struck && overlaps(&ball.bbox, &bat.bbox) {
+                ball.angle = reflect(ball.angle, BAT_NORMAL);
+                ball.velocity = exit_velocity(bat.power);
+                ball.struck = true;
+                let grade = bat.grade.unwrap_or(SwingGrade::Weak);
+                self.sound_tx.send(SoundEvent::BatContact(grade)).unwrap();
+            }
+
+            if !in_bounds(&ball.pos) {
+                ball.out_of_bounds = true;
+            }
+        }
+    }
+}
+
+
+/// Draws the world (through the camera), then the meter over the top of it.
 #[derive(Clone, Debug)]
 pub struct Render {
     pub tx: Sender<DrawCommand>
@@ -212,34 +505,88 @@ pub struct Render {
 impl specs::System<TickData> for Render {
     fn run(&mut self, arg: specs::RunArg, data: TickData) {
 
-        let (batter, pitcher, power_meter, game_flow) = arg.fetch(|w| {
-            (w.read::<Batter>(), w.read::<Pitcher>(), w.read::<PowerMeter>(), w.read::<GameFlow>())
+        let (batter, bats, pitcher, power_meter, game_flow, balls, config, camera) = arg.fetch(|w| {
+            (w.read::<Batter>(), w.read::<Bat>(), w.read::<Pitcher>(), w.read::<PowerMeter>(),
+             w.read::<GameFlow>(), w.read::<Ball>(), w.read_resource::<GameConfig>().clone(),
+             w.read_resource::<Camera>().clone())
         });
+        let layout = &config.layout;
+
+        self.tx.send(DrawCommand::Camera(camera)).unwrap();
+        let (x, y) = layout.background;
+        self.tx.send(DrawCommand::DrawTransformed("background.png".to_string(), Transform::at(x, y))).unwrap();
+
+        let stands_scale = graphics::Point::new(layout.stands_scale, layout.stands_scale);
+        for &(x, y) in &layout.stands {
+            self.tx.send(DrawCommand::DrawSpriteSheetCell(
+                "ship".to_string(),
+                0,
+                Transform { scale: stands_scale, ..Transform::at(x, y) })
+            ).unwrap();
+        }
 
-        for (pitch, bat, meter, flow) in (&pitcher, &batter, &power_meter, &game_flow).iter() {
+        for (pitch, _, bat) in (&pitcher, &batter, &bats).iter() {
 //            println!("Render: {:?}", pitch);
 //            println!("Render: {:?}", bat);
 
             if let Some(ref clip) = pitch.active_clip {
                 if let Some(idx) = clip.get_cell() {
 //                    println!("Clip: nam={}, cell={}", clip.name, idx);
+                    let (x, y) = layout.pitcher;
+                    let scale = layout.pitcher_scale * config.opponent.scale();
                     self.tx.send(DrawCommand::DrawSpriteSheetCell(
-                        "pitching-machine.png".to_string(),
+                        config.opponent.sheet().to_string(),
                         idx,
-                        graphics::Point::new(512., 530.),
-                        graphics::Point::new(2., 2.))
+                        Transform { scale: graphics::Point::new(scale, scale), ..Transform::at(x, y) })
                     ).unwrap();
                 }
 
             }
 
+            let (x, y) = layout.batter;
+            self.tx.send(DrawCommand::DrawSpriteSheetCell(
+                "alien".to_string(),
+                0,
+                Transform { scale: graphics::Point::new(BATTER_SCALE, BATTER_SCALE), ..Transform::at(x, y) })
+            ).unwrap();
+            let (x, y) = layout.cap;
+            self.tx.send(DrawCommand::DrawSpriteSheetCell("cap".to_string(), 0, Transform::at(x, y))).unwrap();
+
+            if let Some(ref clip) = bat.active_clip {
+                if let Some(idx) = clip.get_cell() {
+                    let (hx, hy) = BAT_HANDLE;
+                    let (x, y) = layout.bat;
+                    self.tx.send(DrawCommand::DrawSpriteSheetCell(
+                        "bat".to_string(),
+                        idx,
+                        Transform {
+                            rot: bat_rotation(bat),
+                            origin: graphics::Point::new(hx, hy),
+                            ..Transform::at(x, y)
+                        })
+                    ).unwrap();
+                }
+            }
+        }
+
+        for ball in (&balls).iter() {
+            self.tx.send(DrawCommand::DrawTransformed(
+                "ball.png".to_string(),
+                Transform { rot: ball.rotation, ..Transform::at(ball.pos.x, ball.pos.y) })
+            ).unwrap();
+        }
+
+        // the meter stays put on the screen
+        self.tx.send(DrawCommand::Camera(Camera::plate(config.window.size()))).unwrap();
+
+        for (meter, flow) in (&power_meter, &game_flow).iter() {
             if let Some(ref clip) = meter.active_clip {
                 if let Some(idx) = clip.get_cell() {
+                    let (x, y) = config.window.place(layout.meter);
                     self.tx.send(DrawCommand::DrawSpriteSheetCell(
-                        "bar.png".to_string(),
+                        "bar".to_string(),
                         idx,
-                        graphics::Point::new(200., 700.),
-                        graphics::Point::new(1., 1.))
+                        Transform::at(x, y))
                     ).unwrap();
                 }
 
@@ -249,12 +596,11 @@ impl specs::System<TickData> for Render {
                 GamePhase::Windup | GamePhase::Pitching | GamePhase::BallInFlight => {
                     let ref clip = meter.pointer_clip;
                     if let Some(idx) = clip.get_cell() {
-                        self.tx.send(DrawCommand::DrawSpriteSheetCell(
-                            "pointer.png".to_string(),
-                            idx,
-                            graphics::Point::new(200. + (120. * meter.power_level), 730.),
-                            graphics::Point::new(1., 1.))
-                        ).unwrap();
+                        let (x, y) = layout.pointer;
+                        let (x, y) = config.window.place(
+                            (x + layout.pointer_travel * config.meter.pointer_offset(meter.power_level), y));
+                        self.tx.send(DrawCommand::DrawSpriteSheetCell("pointer".to_string(), idx, Transform::at(x, y)))
+                            .unwrap();
                     }
                 },
                 _ => ()
@@ -263,3 +609,60 @@ impl specs::System<TickData> for Render {
     }
 }
 
+
+/// Score, count and what's going on, drawn over the top of everything else.
+#[derive(Clone, Debug)]
+pub struct Hud {
+    pub tx: Sender<DrawCommand>
+}
+
+impl Hud {
+    /// Send a line of text with its top-left corner at `(x, y)`.
+    fn text(&self, text: String, x: f32, y: f32) {
+        let xform = Transform { origin: graphics::Point::new(0., 0.), ..Transform::at(x, y) };
+        self.tx.send(DrawCommand::DrawText(text, xform)).unwrap();
+    }
+}
+
+impl specs::System<TickData> for Hud {
+    fn run(&mut self, arg: specs::RunArg, _data: TickData) {
+        let (game_flow, bats, rules, score, config) = arg.fetch(|w| {
+            (w.read::<GameFlow>(), w.read::<Bat>(), w.read_resource::<MatchRules>().clone(),
+             w.read_resource::<Score>().clone(), w.read_resource::<GameConfig>().clone())
+        });
+
+        // the lines under each of these are a line of text apart, however big the window is
+        let (score_x, score_y) = config.window.place(config.layout.score);
+        self.text(format!("Score: {}", score.points), score_x, score_y);
+        if score.multiplier() > 1 {
+            self.text(format!("x{} streak!", score.multiplier()), score_x, score_y + 30.);
+        }
+
+        let count = match rules.mode {
+            RulesMode::Derby { outs_per_round } => format!("Outs: {}/{}", rules.outs, outs_per_round),
+            RulesMode::Innings { .. } => format!("Inning: {}  Strikes: {}  Outs: {}",
+                                                 rules.inning, rules.strikes, rules.outs),
+        };
+        let (count_x, count_y) = config.window.place(config.layout.count);
+        self.text(count, count_x, count_y);
+
+        for flow in (&game_flow).iter() {
+            let phase = match flow.active {
+                GamePhase::WaitingForPlayer => "Hold the button to step up".to_string(),
+                GamePhase::PlayerReady => "Batter up!".to_string(),
+                GamePhase::Windup | GamePhase::Pitching | GamePhase::BallInFlight => "Let go to swing!".to_string(),
+                GamePhase::HomeRun => format!("HOME RUN! +{}", score.last_award),
+                GamePhase::Hit => format!("Hit! +{}", score.last_award),
+                GamePhase::Foul => "Foul ball".to_string(),
+                GamePhase::Miss => "Strike!".to_string(),
+            };
+            self.text(phase, count_x, count_y + 30.);
+        }
+
+        for bat in (&bats).iter() {
+            if let Some(grade) = bat.grade {
+                self.text(grade.describe().to_string(), count_x, count_y + 60.);
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Running the simulation at a fixed rate, however fast frames are actually coming in.
+//!
+//! Each frame's real time goes into an accumulator, and the game is ticked once for every whole
+//! step that's built up, always with the same `step_ms`. That way the power meter and the windup
+//! timers behave the same on a slow machine as on a fast one. Whatever is left over (less than a
+//! step) is carried into the next frame, and `alpha` says how far into the next step we are for
+//! anything that wants to interpolate when drawing.
+//!
+//! If a frame takes so long that catching up would take more than `max_steps` ticks, the extra
+//! time is thrown away rather than trying to run ever more ticks per frame (and falling further
+//! behind each time).
+
+use std::time::Duration;
+
+use super::Delta;
+
+
+pub const DEFAULT_TICK_RATE: u32 = 60;
+/// Most ticks to run in a single frame before dropping time.
+pub const DEFAULT_MAX_STEPS: u32 = 5;
+
+/// A `Duration` in (fractional) milliseconds, whole seconds included.
+pub fn duration_ms(duration: Duration) -> Delta {
+    duration.as_secs() as Delta * 1000. + duration.subsec_nanos() as Delta / 1e6
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedTimestep {
+    pub step_ms: Delta,
+    pub max_steps: u32,
+    accumulator: Delta,
+}
+
+impl FixedTimestep {
+    /// Tick `rate` times per second.
+    pub fn new(rate: u32) -> Self {
+        FixedTimestep {
+            step_ms: 1000. / rate.max(1) as Delta,
+            max_steps: DEFAULT_MAX_STEPS,
+            accumulator: 0.,
+        }
+    }
+
+    /// Add a frame's worth of time, and get back how many ticks to run for it.
+    pub fn advance(&mut self, frame_ms: Delta) -> u32 {
+        self.accumulator += frame_ms.max(0.);
+
+        let mut steps = (self.accumulator / self.step_ms).floor() as u32;
+        if steps > self.max_steps {
+            steps = self.max_steps;
+            // too far behind to catch up, so only keep the part of a step we were into
+            self.accumulator %= self.step_ms;
+        } else {
+            self.accumulator -= steps as Delta * self.step_ms;
+        }
+        steps
+    }
+
+    /// How far (0 to 1) we are between the last tick and the next.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step_ms).max(0.).min(1.)
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(DEFAULT_TICK_RATE)
+    }
+}
@@ -0,0 +1,73 @@
+//! Notices when files in a directory change, for reloading assets while the game runs.
+//!
+//! This just polls modification times (and sizes, since some filesystems only keep mtimes to the
+//! second), which is plenty for a handful of sprite sheets and doesn't need a platform API.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::Delta;
+
+
+/// How often a `DirWatcher` looks at the disk when polled every tick.
+pub const WATCH_INTERVAL_MS: Delta = 500.;
+
+pub struct DirWatcher {
+    dir: PathBuf,
+    seen: HashMap<String, (SystemTime, u64)>,
+    since_scan: Delta,
+}
+
+impl DirWatcher {
+    /// Start watching `dir`. Files already there only count once they change.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        let mut watcher = DirWatcher {
+            dir: dir.as_ref().to_path_buf(),
+            seen: HashMap::new(),
+            since_scan: 0.,
+        };
+        watcher.scan();
+        watcher
+    }
+
+    /// Names of the files that appeared or changed since the last scan, in order.
+    pub fn scan(&mut self) -> Vec<String> {
+        let mut changed = vec![];
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                println!("Couldn't watch {}: {}", self.dir.display(), e);
+                return changed;
+            }
+        };
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let stamp = match entry.metadata() {
+                Ok(ref meta) if meta.is_file() => match meta.modified() {
+                    Ok(modified) => (modified, meta.len()),
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if self.seen.get(&name) != Some(&stamp) {
+                self.seen.insert(name.clone(), stamp);
+                changed.push(name);
+            }
+        }
+        changed.sort();
+        changed
+    }
+
+    /// Call every tick: scans once `WATCH_INTERVAL_MS` have gone by, and otherwise finds nothing.
+    pub fn poll(&mut self, delta_ms: Delta) -> Vec<String> {
+        self.since_scan += delta_ms;
+        if self.since_scan < WATCH_INTERVAL_MS {
+            return vec![];
+        }
+        self.since_scan = 0.;
+        self.scan()
+    }
+}
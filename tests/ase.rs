@@ -0,0 +1,113 @@
+extern crate ld38;
+
+use std::fs::File;
+use std::io::Read;
+
+use ld38::ase::{AseError, AseFile, BlendMode, Direction, Tag};
+
+
+fn bytes(path: &str) -> Vec<u8> {
+    let mut bytes = vec![];
+    File::open(path).unwrap().read_to_end(&mut bytes).unwrap();
+    bytes
+}
+
+fn tag(name: &str, from: usize, to: usize, direction: Direction) -> Tag {
+    Tag { name: name.to_string(), from: from, to: to, direction: direction }
+}
+
+#[test]
+fn reads_frames_and_tags() {
+    let bat = AseFile::load("resources/bat.ase").unwrap();
+    assert_eq!((bat.width, bat.height, bat.depth), (128, 128, 32));
+    assert_eq!(bat.frames.iter().map(|f| f.duration).collect::<Vec<_>>(), vec![200, 200, 500]);
+    assert_eq!(bat.tags, vec![
+        tag("Swing", 0, 2, Direction::Forward),
+        tag("Backswing", 0, 1, Direction::Reverse),
+        tag("Idle", 0, 0, Direction::Forward),
+    ]);
+
+    let machine = AseFile::load("resources/pitching-machine.ase").unwrap();
+    assert_eq!(machine.tags.last().unwrap(), &tag("Not Ready", 12, 15, Direction::PingPong));
+}
+
+#[test]
+fn reads_layers() {
+    let ship = AseFile::load("resources/ship.ase").unwrap();
+    let names: Vec<&str> = ship.layers.iter().map(|l| l.name.as_ref()).collect();
+    assert_eq!(names, vec!["Back wall", "Inside", "Chairs", "Ship", "Base Copy", "Lines", "Viewport",
+                           "Viewport frame", "Lights"]);
+
+    let base_copy = &ship.layers[4];
+    assert!(!base_copy.visible);
+    let viewport = &ship.layers[6];
+    assert_eq!((viewport.blend, viewport.opacity), (BlendMode::Overlay, 163));
+}
+
+#[test]
+fn flattens_frames_like_the_export() {
+    // pixels checked against the PNGs Aseprite exported
+    let bat = AseFile::load("resources/bat.ase").unwrap().render_frame(0);
+    assert_eq!((bat.width, bat.height), (128, 128));
+    assert_eq!(bat.pixel(52, 54), [255, 162, 0, 255]);
+    assert_eq!(bat.pixel(0, 0), [0, 0, 0, 0]);
+    assert_eq!(bat.rgba.chunks(4).filter(|px| px[3] == 255).count(), 1518);
+
+    let machine = AseFile::load("resources/pitching-machine.ase").unwrap().render_frame(5);
+    assert_eq!(machine.pixel(25, 13), [121, 121, 121, 255]);
+}
+
+#[test]
+fn export_puts_frames_side_by_side() {
+    let pointer = AseFile::load("resources/pointer.ase").unwrap();
+    let (atlas, json) = pointer.export("pointer.ase");
+    assert_eq!((atlas.width, atlas.height), (64, 32));
+    assert_eq!(atlas.pixel(32 + 10, 10), pointer.render_frame(1).pixel(10, 10));
+
+    assert!(json.contains("\"frameTags\""), "{}", json);
+    assert!(json.contains("\"image\": \"pointer.ase\""), "{}", json);
+    assert!(json.contains("\"filename\": \"pointer 1.ase\""), "{}", json);
+    assert!(json.contains("\"x\": 32"), "{}", json);
+}
+
+#[test]
+fn long_animations_are_packed_into_a_grid() {
+    let pitcher = AseFile::load("resources/pitcher.ase").unwrap();
+    let (atlas, json) = pitcher.export("pitcher.ase");
+    // 21 frames, five to a row
+    assert_eq!((atlas.width, atlas.height), (5 * 256, 5 * 256));
+    assert_eq!(atlas.pixel(2 * 256 + 100, 2 * 256 + 100), pitcher.render_frame(12).pixel(100, 100));
+    assert_eq!(atlas.pixel(100, 4 * 256 + 100), pitcher.render_frame(20).pixel(100, 100));
+    assert!(json.contains("\"filename\": \"pitcher 20.ase\""), "{}", json);
+    assert!(json.contains("\"y\": 1024"), "{}", json);
+}
+
+#[test]
+fn rejects_other_files() {
+    match AseFile::parse(&bytes("resources/bat.json")) {
+        Err(AseError::BadMagic) => (),
+        other => panic!("expected bad magic, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn notices_truncated_files() {
+    let whole = bytes("resources/bat.ase");
+    for &len in &[10, 200, whole.len() - 1] {
+        match AseFile::parse(&whole[..len]) {
+            Err(AseError::Truncated) => (),
+            other => panic!("cut at {}: expected truncated, got {:?}", len, other.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn unsupported_color_depth() {
+    let mut file = bytes("resources/bat.ase");
+    // the depth lives just after the frame count, width and height
+    file[12] = 24;
+    match AseFile::parse(&file) {
+        Err(AseError::Unsupported(ref what)) if what.contains("24") => (),
+        other => panic!("expected unsupported, got {:?}", other.map(|_| ())),
+    }
+}
@@ -0,0 +1,103 @@
+extern crate ld38;
+extern crate specs;
+
+mod common;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use common::Harness;
+use ld38::GamePhase;
+use ld38::audio::{AudioSettings, SoundEvent, AUDIO_SETTINGS_FILE, VOLUME_STEP};
+use ld38::config::GameConfig;
+use ld38::headless::{self, InputSource, ScriptedInput};
+use ld38::meter::SwingGrade;
+
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("hwd-audio-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn windup_then_launch() {
+    let mut h = Harness::new(3);
+    h.run_until(GamePhase::Windup, 200., true).expect("never wound up");
+    assert_eq!(h.audio.played, vec![SoundEvent::WindupHum]);
+
+    h.pitch();
+    assert_eq!(h.audio.played, vec![SoundEvent::WindupHum, SoundEvent::PitchLaunch]);
+}
+
+#[test]
+fn contact_sounds_like_the_swing_and_home_runs_get_a_cheer() {
+    let mut h = common::perfect_home_run();
+    h.run_until(GamePhase::HomeRun, 6000., false).expect("never went out");
+
+    assert_eq!(h.audio.played, vec![
+        SoundEvent::WindupHum,
+        SoundEvent::PitchLaunch,
+        SoundEvent::BatContact(SwingGrade::Perfect),
+        SoundEvent::CrowdCheer,
+    ]);
+}
+
+#[test]
+fn no_cheer_for_a_miss() {
+    let mut h = Harness::new(7);
+    h.pitch();
+    h.run_until(GamePhase::Miss, 3000., true).expect("ball never left play");
+    assert_eq!(h.audio.played, vec![SoundEvent::WindupHum, SoundEvent::PitchLaunch]);
+}
+
+#[test]
+fn headless_report_lists_sounds() {
+    let script = InputSource::Script(ScriptedInput::default_script());
+    let report = headless::run(42, 600, script, &GameConfig::default()).unwrap();
+
+    let sounds: Vec<SoundEvent> = report.sounds.iter().map(|&(_, ref sound)| sound.clone()).collect();
+    assert_eq!(&sounds[..2], &[SoundEvent::WindupHum, SoundEvent::PitchLaunch]);
+    // the hum starts as soon as the button goes down
+    assert!(report.sounds[0].0 >= 500. && report.sounds[0].0 <= 500. + 2. * headless::DEFAULT_DELTA_MS);
+}
+
+#[test]
+fn volume_stays_in_range_and_unmutes() {
+    let mut settings = AudioSettings::default();
+    settings.toggle_mute();
+    assert_eq!(settings.effective_volume(), 0.);
+
+    settings.adjust(-VOLUME_STEP);
+    assert!(!settings.muted);
+    assert!(settings.effective_volume() > 0.);
+
+    for _ in 0..20 {
+        settings.adjust(VOLUME_STEP);
+    }
+    assert_eq!(settings.volume, 1.);
+    for _ in 0..20 {
+        settings.adjust(-VOLUME_STEP);
+    }
+    assert_eq!(settings.volume, 0.);
+}
+
+#[test]
+fn settings_round_trip_through_the_data_dir() {
+    let dir = scratch_dir("round-trip");
+    assert_eq!(AudioSettings::load(&dir), AudioSettings::default());
+
+    let settings = AudioSettings { volume: 0.3, muted: true };
+    settings.save(&dir).unwrap();
+    assert_eq!(AudioSettings::load(&dir), settings);
+}
+
+#[test]
+fn broken_settings_fall_back_to_the_defaults() {
+    let dir = scratch_dir("broken");
+    File::create(dir.join(AUDIO_SETTINGS_FILE)).unwrap().write_all(b"volume = \"loud\"").unwrap();
+    assert_eq!(AudioSettings::load(&dir), AudioSettings::default());
+}
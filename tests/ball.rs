@@ -0,0 +1,168 @@
+extern crate ggez;
+extern crate ld38;
+extern crate omn_labs;
+extern crate specs;
+
+mod common;
+
+use std::f32::consts::PI;
+use std::sync::mpsc::channel;
+
+use ggez::graphics::{Point, Rect};
+use specs::Join;
+
+use common::Harness;
+use ld38::{GamePhase, TickData};
+use ld38::components::{Ball, Bat};
+use ld38::config::GameConfig;
+use ld38::meter::SwingGrade;
+use ld38::pitches::Difficulty;
+use ld38::systems::{bat_bbox, judge_pitch, overlaps, reflect, swing_boost, wrap_angle, BallPhysics, BAT_BOOST,
+                    BAT_NORMAL, HOME_RUN_VELOCITY, PITCH_VELOCITY};
+
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 0.0001
+}
+
+/// The game's bat, mid-swing as hard as it goes (or resting).
+fn bat(swinging: bool) -> Bat {
+    let mut bat = Harness::new(1).bat().expect("no bat in the world");
+    bat.swinging = swinging;
+    bat.power = 1.;
+    bat
+}
+
+/// A pitch just short of the middle of the bat.
+fn pitch_at(bat: &Bat) -> Ball {
+    Ball::new(Point::new(bat.bbox.x + bat.bbox.w / 2., bat.bbox.y - 10.), PI / 2., PITCH_VELOCITY)
+}
+
+/// Run `BallPhysics` alone for one tick over a bat and a ball, handing back the ball.
+fn bounce(bat: Bat, ball: Ball) -> Ball {
+    let mut world = specs::World::new();
+    world.register::<Bat>();
+    world.register::<Ball>();
+    world.create_now().with(bat).with(ball).build();
+
+    let (sound_tx, _sound_rx) = channel();
+    let mut plan = specs::Planner::new(world, 1);
+    plan.add_system(BallPhysics { sound_tx: sound_tx }, "ball", 20);
+    plan.dispatch(TickData { delta_ms: 16., ..TickData::new() });
+    plan.wait();
+
+    let world = plan.mut_world();
+    let balls = world.read::<Ball>();
+    let ball = (&balls).iter().next().cloned().unwrap();
+    ball
+}
+
+#[test]
+fn overlapping_boxes() {
+    let bat = Rect::new(432., 640., 160., 40.);
+    assert!(overlaps(&Rect::new(500., 630., 16., 16.), &bat));
+    assert!(!overlaps(&Rect::new(500., 600., 16., 16.), &bat));
+    assert!(!overlaps(&Rect::new(600., 650., 16., 16.), &bat));
+}
+
+#[test]
+fn bat_hitbox_follows_the_handle() {
+    let mut config = GameConfig::default();
+    assert_eq!(bat_bbox(&config.layout), Rect::new(432., 640., 160., 40.));
+
+    config.layout.bat = (580., 600.);
+    assert_eq!(bat_bbox(&config.layout), Rect::new(532., 548., 160., 40.));
+    assert_eq!(Harness::with_config(1, &config).bat().unwrap().bbox, bat_bbox(&config.layout));
+}
+
+#[test]
+fn straight_pitch_reflects_straight_back() {
+    assert!(close(reflect(PI / 2., BAT_NORMAL), -PI / 2.));
+}
+
+#[test]
+fn angled_pitch_reflects_across_the_normal() {
+    // coming in down and to the right leaves up and to the right
+    let incoming = PI / 2. - 0.3;
+    assert!(close(reflect(incoming, BAT_NORMAL), -PI / 2. + 0.3));
+}
+
+#[test]
+fn wrapped_angles_stay_in_range() {
+    assert!(close(wrap_angle(3. * PI / 2.), -PI / 2.));
+    assert!(close(wrap_angle(-3. * PI / 2.), PI / 2.));
+}
+
+#[test]
+fn swing_power_scales_boost() {
+    assert!(close(swing_boost(1.), BAT_BOOST));
+    assert!(swing_boost(0.) > 0.);
+    assert!(swing_boost(0.5) < swing_boost(1.));
+    assert!(close(swing_boost(2.), BAT_BOOST));
+}
+
+#[test]
+fn swinging_bat_sends_the_ball_back() {
+    let bat = bat(true);
+    let ball = bounce(bat.clone(), pitch_at(&bat));
+    assert!(ball.struck);
+    assert!(close(ball.angle, BAT_NORMAL), "angle was {}", ball.angle);
+    assert!(close(ball.velocity, PITCH_VELOCITY * BAT_BOOST), "velocity was {}", ball.velocity);
+}
+
+#[test]
+fn resting_bat_lets_the_ball_by() {
+    let bat = bat(false);
+    let ball = bounce(bat.clone(), pitch_at(&bat));
+    assert!(!ball.struck);
+    assert!(close(ball.angle, PI / 2.));
+    assert!(close(ball.velocity, PITCH_VELOCITY));
+}
+
+#[test]
+fn struck_balls_only_bounce_once() {
+    let bat = bat(true);
+    let mut ball = pitch_at(&bat);
+    ball.angle = BAT_NORMAL;
+    ball.velocity = PITCH_VELOCITY * BAT_BOOST;
+    ball.pos.y = bat.bbox.y + 10.;
+    ball.struck = true;
+
+    let ball = bounce(bat, ball);
+    assert!(close(ball.angle, BAT_NORMAL));
+    assert!(close(ball.velocity, PITCH_VELOCITY * BAT_BOOST));
+}
+
+#[test]
+fn solid_contact_can_leave_the_park() {
+    let bat = bat(true);
+    let ball = bounce(bat.clone(), pitch_at(&bat));
+    assert!(ball.velocity >= HOME_RUN_VELOCITY, "came off the bat at {}", ball.velocity);
+}
+
+#[test]
+fn every_difficulty_can_be_hit_out() {
+    for difficulty in &[Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+        for (_, spec) in difficulty.pitch_mix() {
+            let mut bat = bat(true);
+            bat.power = SwingGrade::Perfect.strength();
+            let mut ball = pitch_at(&bat);
+            ball.velocity = spec.speed;
+
+            let mut ball = bounce(bat, ball);
+            ball.out_of_bounds = true;
+            assert_eq!(judge_pitch(&ball), Some(GamePhase::HomeRun),
+                       "{:?} {:?} came off the bat at {}", difficulty, spec.kind, ball.velocity);
+        }
+    }
+}
+
+#[test]
+fn pitch_speed_doesnt_change_how_far_it_goes() {
+    let bat = bat(true);
+    let mut slow = pitch_at(&bat);
+    slow.velocity = PITCH_VELOCITY / 2.;
+    let mut fast = pitch_at(&bat);
+    fast.velocity = PITCH_VELOCITY * 1.5;
+    assert!(close(bounce(bat.clone(), slow).velocity, bounce(bat, fast).velocity));
+}
@@ -0,0 +1,127 @@
+extern crate ggez;
+extern crate ld38;
+extern crate specs;
+
+mod common;
+
+use ggez::graphics::Point;
+
+use ld38::GamePhase;
+use ld38::camera::{Camera, LONG_DRIVE_ZOOM};
+use ld38::components::Ball;
+use ld38::config::GameConfig;
+use ld38::render::Transform;
+
+
+/// The default window.
+const VIEW: (f32, f32) = (1024., 768.);
+
+fn close(a: Point, b: Point) -> bool {
+    (a.x - b.x).abs() < 0.001 && (a.y - b.y).abs() < 0.001
+}
+
+#[test]
+fn plate_view_is_the_identity() {
+    for &view in &[VIEW, (1280., 960.), (640., 480.)] {
+        let camera = Camera::plate(view);
+        for &(x, y) in &[(0., 0.), (512., 384.), (-300., 2000.)] {
+            assert!(close(camera.world_to_screen(Point::new(x, y)), Point::new(x, y)));
+        }
+
+        let xform = Transform { rot: 1., ..Transform::at(10., 20.) };
+        assert_eq!(camera.apply(&xform), xform);
+    }
+}
+
+#[test]
+fn center_goes_to_the_middle_of_the_screen() {
+    let camera = Camera::new(Point::new(900., -1200.), 0.5, VIEW);
+    assert!(close(camera.world_to_screen(Point::new(900., -1200.)), Point::new(512., 384.)));
+
+    let wide = Camera::new(Point::new(900., -1200.), 0.5, (1600., 900.));
+    assert!(close(wide.world_to_screen(Point::new(900., -1200.)), Point::new(800., 450.)));
+}
+
+#[test]
+fn the_camera_uses_the_configured_window() {
+    let mut config = GameConfig::default();
+    config.window.width = 1600;
+    config.window.height = 900;
+    let mut h = common::Harness::with_config(1, &config);
+    assert_eq!(h.camera(), Camera::plate((1600., 900.)));
+}
+
+#[test]
+fn zooming_out_brings_things_closer_together() {
+    let camera = Camera::new(Point::new(512., 384.), 0.5, VIEW);
+    assert!(close(camera.world_to_screen(Point::new(712., 384.)), Point::new(612., 384.)));
+    assert!(close(camera.world_to_screen(Point::new(512., 0.)), Point::new(512., 192.)));
+
+    let xform = camera.apply(&Transform { scale: Point::new(2., 2.), ..Transform::at(712., 384.) });
+    assert!(close(xform.pos, Point::new(612., 384.)));
+    assert_eq!(xform.scale, Point::new(1., 1.));
+}
+
+#[test]
+fn screen_to_world_undoes_world_to_screen() {
+    let camera = Camera::new(Point::new(-250., 1700.), 0.4, VIEW);
+    for &(x, y) in &[(0., 0.), (1024., 768.), (33., 600.)] {
+        let screen = Point::new(x, y);
+        assert!(close(camera.world_to_screen(camera.screen_to_world(screen)), screen));
+    }
+}
+
+#[test]
+fn easing_gets_there_at_any_frame_rate() {
+    let target = Camera::new(Point::new(512., -1500.), LONG_DRIVE_ZOOM, VIEW);
+
+    let mut coarse = Camera::plate(VIEW);
+    coarse.ease_toward(&target, 100.);
+    let mut fine = Camera::plate(VIEW);
+    for _ in 0..10 {
+        fine.ease_toward(&target, 10.);
+    }
+    assert!(close(coarse.center, fine.center));
+    assert!((coarse.zoom - fine.zoom).abs() < 0.001);
+
+    // partway there, without overshooting
+    assert!(coarse.center.y < 384. && coarse.center.y > -1500.);
+    for _ in 0..100 {
+        coarse.ease_toward(&target, 100.);
+    }
+    assert!(close(coarse.center, target.center));
+    assert!((coarse.zoom - LONG_DRIVE_ZOOM).abs() < 0.001);
+}
+
+#[test]
+fn only_hit_balls_are_followed() {
+    let mut ball = Ball::new(Point::new(512., 600.), 0., 1.);
+    let plate = Camera::plate(VIEW);
+    assert_eq!(plate.target(None), plate);
+    assert_eq!(plate.target(Some(&ball)), plate);
+
+    ball.struck = true;
+    let target = plate.target(Some(&ball));
+    assert_eq!(target.center, ball.pos);
+    assert_eq!(target.zoom, LONG_DRIVE_ZOOM);
+
+    ball.velocity = 0.3;
+    assert_eq!(plate.target(Some(&ball)).zoom, 1.);
+}
+
+#[test]
+fn follows_a_long_drive_then_returns_to_the_plate() {
+    let mut h = common::perfect_home_run();
+    assert_eq!(h.camera(), Camera::plate(VIEW));
+
+    h.run_until(GamePhase::HomeRun, 6000., false).expect("never went out");
+    let out_there = h.camera();
+    assert!(out_there.center.y < 0., "camera stayed at {:?}", out_there.center);
+    assert!(out_there.zoom < 1.);
+
+    h.run_until(GamePhase::WaitingForPlayer, 6000., false).expect("never reset");
+    h.step_for(5000., false);
+    let back = h.camera();
+    assert!(close(back.center, Camera::plate(VIEW).center), "camera stuck at {:?}", back.center);
+    assert!((back.zoom - 1.).abs() < 0.001);
+}
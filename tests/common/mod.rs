@@ -0,0 +1,232 @@
+//! Shared harness for driving the simulation from integration tests.
+//!
+//! Builds the world from the real sheets in `resources/` and steps it with scripted `TickData`,
+//! keeping a timeline of every phase change so scenarios can assert on transitions and timing.
+
+#![allow(dead_code)]
+
+use std::sync::mpsc::{channel, Receiver};
+
+use specs::Join;
+
+use ld38::audio::{AudioBackend, NullAudio, SoundEvent};
+use ld38::camera::Camera;
+use ld38::{ECS, GamePhase, InputState, TickData};
+use ld38::components::{Ball, Bat, Pitcher, PowerMeter};
+use ld38::config::GameConfig;
+use ld38::headless::DEFAULT_DELTA_MS;
+use ld38::meter::MeterModel;
+use ld38::pitches::PitchType;
+use ld38::rules::{MatchRules, DEFAULT_DERBY_OUTS};
+use ld38::sheets::{SheetRegistry, DEFAULT_SHEETS};
+use ld38::systems::DrawCommand;
+
+pub struct Harness {
+    pub ecs: ECS,
+    pub rx: Receiver<DrawCommand>,
+    sound_rx: Receiver<SoundEvent>,
+    /// Plays (well, keeps) every sound the systems asked for.
+    pub audio: NullAudio,
+    pub delta_ms: f32,
+    pub elapsed_ms: f32,
+    /// `(time_ms, phase)` for every phase change, starting with the initial phase.
+    pub timeline: Vec<(f32, GamePhase)>,
+    down: bool,
+}
+
+impl Harness {
+    pub fn new(seed: u32) -> Self {
+        Self::with_config(seed, &GameConfig::default())
+    }
+
+    pub fn with_config(seed: u32, config: &GameConfig) -> Self {
+        let sheets = SheetRegistry::load("resources", DEFAULT_SHEETS).unwrap();
+
+        let (tx, rx) = channel::<DrawCommand>();
+        let (sound_tx, sound_rx) = channel::<SoundEvent>();
+        let mut ecs = ECS::new(seed, tx, sound_tx, &sheets, MatchRules::derby(DEFAULT_DERBY_OUTS), config).unwrap();
+        let phase = ecs.phase().expect("no game flow in the world");
+
+        Harness {
+            ecs: ecs,
+            rx: rx,
+            sound_rx: sound_rx,
+            audio: NullAudio::default(),
+            delta_ms: DEFAULT_DELTA_MS,
+            elapsed_ms: 0.,
+            timeline: vec![(0., phase)],
+            down: false,
+        }
+    }
+
+    pub fn phase(&mut self) -> GamePhase {
+        self.ecs.phase().expect("no game flow in the world")
+    }
+
+    /// Run a single tick with the button held (or not).
+    pub fn step(&mut self, down: bool) {
+        let input_state = match (self.down, down) {
+            (false, true) => InputState::JustPressed,
+            (true, true) => InputState::Pressed,
+            (true, false) => InputState::JustReleased,
+            (false, false) => InputState::Released,
+        };
+        self.down = down;
+
+        self.elapsed_ms += self.delta_ms;
+        self.ecs.tick(TickData::swing(self.delta_ms, input_state));
+        // nothing reads these, so don't let them pile up
+        for _ in self.rx.try_iter() {}
+        for sound in self.sound_rx.try_iter() {
+            self.audio.play(&sound);
+        }
+
+        let phase = self.phase();
+        if self.timeline.last().map_or(true, |&(_, ref last)| *last != phase) {
+            self.timeline.push((self.elapsed_ms, phase));
+        }
+    }
+
+    /// Hold (or leave) the button for at least `ms`.
+    pub fn step_for(&mut self, ms: f32, down: bool) {
+        let until = self.elapsed_ms + ms;
+        while self.elapsed_ms < until {
+            self.step(down);
+        }
+    }
+
+    /// Step until `phase` is reached, giving up after `max_ms`. Returns the time it was reached.
+    pub fn run_until(&mut self, phase: GamePhase, max_ms: f32, down: bool) -> Option<f32> {
+        let until = self.elapsed_ms + max_ms;
+        while self.elapsed_ms < until {
+            self.step(down);
+            if self.phase() == phase {
+                return Some(self.elapsed_ms);
+            }
+        }
+        None
+    }
+
+    /// When the timeline entered `phase` (the first time), if it has.
+    pub fn entered(&self, phase: GamePhase) -> Option<f32> {
+        self.timeline.iter().find(|&&(_, ref p)| *p == phase).map(|&(time, _)| time)
+    }
+
+    /// The phases visited so far, in order.
+    pub fn phases(&self) -> Vec<GamePhase> {
+        self.timeline.iter().map(|&(_, ref phase)| phase.clone()).collect()
+    }
+
+    pub fn ball(&mut self) -> Option<Ball> {
+        let world = self.ecs.planner.mut_world();
+        let balls = world.read::<Ball>();
+        let ball = (&balls).iter().next().cloned();
+        ball
+    }
+
+    pub fn bat(&mut self) -> Option<Bat> {
+        let world = self.ecs.planner.mut_world();
+        let bats = world.read::<Bat>();
+        let bat = (&bats).iter().next().cloned();
+        bat
+    }
+
+    /// Where the power meter is at (0 to 1).
+    pub fn meter_level(&mut self) -> f32 {
+        let world = self.ecs.planner.mut_world();
+        let meters = world.read::<PowerMeter>();
+        let level = (&meters).iter().next().expect("no power meter in the world").power_level;
+        level
+    }
+
+    pub fn camera(&mut self) -> Camera {
+        self.ecs.camera()
+    }
+
+    /// The pitch the machine picked for this at-bat, once it's started winding up.
+    pub fn pitch_type(&mut self) -> Option<PitchType> {
+        let world = self.ecs.planner.mut_world();
+        let pitchers = world.read::<Pitcher>();
+        let kind = (&pitchers).iter().next().and_then(|p| p.pitch.as_ref().map(|spec| spec.kind));
+        kind
+    }
+
+    /// Step up to the plate and hold the button until the ball is on its way.
+    pub fn pitch(&mut self) -> f32 {
+        self.run_until(GamePhase::BallInFlight, 10000., true).expect("pitch never came")
+    }
+
+    /// Hold the button until `ready` (given the phase and the meter level after each tick) says to
+    /// let go, then let go. Returns the phase it was let go in, or `None` if the pitch was called
+    /// first or `max_ms` went by.
+    pub fn release_when<F>(&mut self, max_ms: f32, mut ready: F) -> Option<GamePhase>
+        where F: FnMut(&GamePhase, f32) -> bool
+    {
+        let until = self.elapsed_ms + max_ms;
+        while self.elapsed_ms < until {
+            self.step(true);
+            let phase = self.phase();
+            match phase {
+                GamePhase::Foul | GamePhase::HomeRun | GamePhase::Hit | GamePhase::Miss => return None,
+                _ => (),
+            }
+            if ready(&phase, self.meter_level()) {
+                self.step(false);
+                return Some(phase);
+            }
+        }
+        None
+    }
+}
+
+/// Whether the batter can swing in `phase`.
+pub fn can_swing(phase: &GamePhase) -> bool {
+    match *phase {
+        GamePhase::Windup | GamePhase::Pitching | GamePhase::BallInFlight => true,
+        _ => false
+    }
+}
+
+/// Whether `level` is well inside the default meter's perfect window. The batter reads the meter
+/// on the tick the button comes up, which can be a tick on from the level seen here, so this
+/// leaves a tick's worth of the window either side.
+pub fn safely_perfect(level: f32) -> bool {
+    let model = MeterModel::default();
+    (level - model.sweet_spot).abs() <= model.perfect_window / 3.
+}
+
+/// Hold the button from the start and let go the `nth` time (counting from 0) the meter comes into
+/// the perfect window while a swing would count, if that happens before the pitch is called.
+pub fn perfect_swing(seed: u32, nth: usize) -> Option<Harness> {
+    let mut h = Harness::new(seed);
+    let mut was_perfect = false;
+    let mut seen = 0;
+    let released = h.release_when(10000., |phase, level| {
+        let perfect = can_swing(phase) && safely_perfect(level);
+        let entering = perfect && !was_perfect;
+        was_perfect = perfect;
+        if entering {
+            seen += 1;
+        }
+        entering && seen > nth
+    });
+    released.map(|_| h)
+}
+
+/// A harness where the batter just let go with perfect timing, going by the real meter, and is
+/// about to hit the pitch out of the park.
+pub fn perfect_home_run() -> Harness {
+    for seed in 0..100 {
+        for nth in 0.. {
+            let mut trial = match perfect_swing(seed, nth) {
+                Some(trial) => trial,
+                None => break,
+            };
+            if trial.run_until(GamePhase::HomeRun, 6000., false).is_some() {
+                // the same seed and input play out the same way again
+                return perfect_swing(seed, nth).unwrap();
+            }
+        }
+    }
+    panic!("no perfect swing went out in 100 seeds");
+}
@@ -0,0 +1,68 @@
+extern crate ld38;
+
+use ld38::config::{ConfigError, GameConfig, Opponent, LAYOUT_SIZE};
+
+
+#[test]
+fn shipped_config_matches_the_defaults() {
+    assert_eq!(GameConfig::load("resources/config.toml").unwrap(), GameConfig::default());
+}
+
+#[test]
+fn missing_keys_get_defaults() {
+    let config = GameConfig::parse("[timing]\nwindup_min_ms = 2000.0\n").unwrap();
+    assert_eq!(config.timing.windup_min_ms, 2000.);
+    assert_eq!(config.timing.windup_range_ms, GameConfig::default().timing.windup_range_ms);
+    assert_eq!(config.window, GameConfig::default().window);
+
+    assert_eq!(GameConfig::parse("").unwrap(), GameConfig::default());
+}
+
+#[test]
+fn positions_are_pairs() {
+    let config = GameConfig::parse("[layout]\nbat = [100.0, 200.0]\n").unwrap();
+    assert_eq!(config.layout.bat, (100., 200.));
+}
+
+#[test]
+fn screen_positions_fit_the_window() {
+    let mut config = GameConfig::default();
+    assert_eq!(config.window.place(config.layout.meter), config.layout.meter);
+
+    config.window.width = 2048;
+    config.window.height = 1536;
+    assert_eq!(config.window.place((512., 384.)), (1024., 768.));
+    assert_eq!(config.window.place(LAYOUT_SIZE), (2048., 1536.));
+}
+
+#[test]
+fn picks_an_opponent() {
+    assert_eq!(GameConfig::default().opponent, Opponent::Machine);
+    assert_eq!(GameConfig::parse("opponent = \"alien\"\n").unwrap().opponent, Opponent::Alien);
+    assert_eq!(Opponent::parse("alien"), Some(Opponent::Alien));
+    assert_eq!(Opponent::parse("robot"), None);
+}
+
+fn error(text: &str) -> String {
+    match GameConfig::parse(text) {
+        Ok(config) => panic!("expected an error, got {:?}", config),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[test]
+fn errors_name_the_key() {
+    let typo = error("[timing]\nwindup_mn_ms = 2000.0\n");
+    assert!(typo.contains("windup_mn_ms"), "{}", typo);
+
+    let wrong_type = error("[window]\nwidth = \"wide\"\n");
+    assert!(wrong_type.contains("width"), "{}", wrong_type);
+
+    let negative = error("[timing]\ncall_hold_ms = -1.0\n");
+    assert!(negative.contains("timing.call_hold_ms"), "{}", negative);
+
+    match GameConfig::parse("[timing]\ntick_rate = 0\n") {
+        Err(ConfigError::Invalid { ref key, .. }) if key == "timing.tick_rate" => (),
+        other => panic!("expected a bad tick rate, got {:?}", other),
+    }
+}
@@ -0,0 +1,120 @@
+extern crate ld38;
+extern crate omn_labs;
+
+use std::sync::mpsc::channel;
+
+use omn_labs::sprites::SpriteSheetData;
+use ld38::ECS;
+use ld38::config::GameConfig;
+use ld38::error::AssetError;
+use ld38::rules::MatchRules;
+use ld38::sheets::{load_ase, SheetError, SheetRegistry, DEFAULT_SHEETS};
+use ld38::systems::BAT_CLIPS;
+
+
+/// Deliberately broken sheets, one problem apiece.
+const FIXTURES: &'static str = "tests/fixtures/sheets";
+
+fn load_fixture(id: &str) -> Result<(), AssetError> {
+    SheetRegistry::new().load_export(FIXTURES, id)
+}
+
+fn malformed_because(id: &str) -> String {
+    match load_fixture(id) {
+        Err(AssetError::MalformedSheet { path, reason }) => {
+            assert_eq!(path, format!("{}/{}.json", FIXTURES, id));
+            reason
+        },
+        other => panic!("expected {} to be malformed, got {:?}", id, other),
+    }
+}
+
+#[test]
+fn missing_sheet() {
+    assert_eq!(load_fixture("nothing"), Err(AssetError::Missing(format!("{}/nothing.json", FIXTURES))));
+}
+
+#[test]
+fn missing_image() {
+    assert_eq!(load_fixture("no-image"), Err(AssetError::Missing(format!("{}/no-image.png", FIXTURES))));
+}
+
+#[test]
+fn truncated_json() {
+    let reason = malformed_because("truncated");
+    assert!(reason.contains("EOF"), "{}", reason);
+}
+
+#[test]
+fn json_without_tags() {
+    let reason = malformed_because("no-tags");
+    assert!(reason.contains("frameTags"), "{}", reason);
+}
+
+#[test]
+fn tags_past_the_last_frame() {
+    let reason = malformed_because("tag-past-the-end");
+    assert_eq!(reason, "tag `Swing` covers frames 0 to 5, but there are 3 frames");
+}
+
+#[test]
+fn ase_tags_past_the_last_frame() {
+    let path = format!("{}/tag-past-the-end.ase", FIXTURES);
+    match load_ase(&path, "tag-past-the-end.ase") {
+        Err(AssetError::MalformedSheet { path: ref bad, ref reason }) => {
+            assert_eq!(bad, &path);
+            assert_eq!(reason, "tag `Swing` covers frames 0 to 5, but there are 3 frames");
+        },
+        other => panic!("expected the .ase to be malformed, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn frames_off_the_image() {
+    let reason = malformed_because("off-the-image");
+    assert_eq!(reason, "frame 2 runs off the 256x256 image");
+}
+
+#[test]
+fn sheets_fail_to_load_as_a_whole() {
+    match SheetRegistry::load(FIXTURES, &["idle-bat", "no-tags"]) {
+        Err(AssetError::MalformedSheet { ref path, .. }) if path.ends_with("no-tags.json") => (),
+        other => panic!("expected no-tags to stop the load, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn missing_clips_name_the_file_and_tag() {
+    let mut sheets = SheetRegistry::new();
+    sheets.load_export(FIXTURES, "idle-bat").unwrap();
+    let missing = AssetError::MissingClip {
+        path: format!("{}/idle-bat.json", FIXTURES),
+        tag: "Swing".to_string(),
+    };
+    assert_eq!(sheets.require_clips("idle-bat", BAT_CLIPS).err(), Some(missing.clone()));
+    assert_eq!(missing.to_string(), "tests/fixtures/sheets/idle-bat.json has no animation tagged `Swing`");
+
+    assert_eq!(sheets.require_clips("bat", BAT_CLIPS).err(),
+               Some(AssetError::Sheet(SheetError::UnknownSheet("bat".to_string()))));
+}
+
+#[test]
+fn the_game_wont_start_without_its_animations() {
+    let mut sheets = SheetRegistry::load("resources", DEFAULT_SHEETS).unwrap();
+    let config = GameConfig::default();
+    assert!(ECS::check_sheets(&sheets, &config).is_ok());
+
+    // a bat that can't swing
+    let path = format!("{}/idle-bat.json", FIXTURES);
+    sheets.insert("bat", SpriteSheetData::from_file(&path), "idle-bat.png", &path);
+
+    let (tx, _rx) = channel();
+    let (sound_tx, _sound_rx) = channel();
+    match ECS::new(1, tx, sound_tx, &sheets, MatchRules::derby(1), &config) {
+        Ok(_) => panic!("started a game with a bat that can't swing"),
+        Err(e) => {
+            let message = e.to_string();
+            assert!(message.contains("idle-bat.json") && message.contains("Swing"), "{}", message);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+extern crate ld38;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use ld38::highscores::{clean_initials, HighScore, HighScoreError, HighScoreTable, HighScores, HIGH_SCORE_FILE};
+
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("hwd-highscores-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(dir: &PathBuf, name: &str, text: &str) {
+    File::create(dir.join(name)).unwrap().write_all(text.as_bytes()).unwrap();
+}
+
+#[test]
+fn best_scores_first_and_capped() {
+    let mut table = HighScoreTable::new(3);
+    assert_eq!(table.insert(HighScore::new(100, 1, "2017-04-22", "AAA")), Some(0));
+    assert_eq!(table.insert(HighScore::new(300, 2, "2017-04-22", "BBB")), Some(0));
+    assert_eq!(table.insert(HighScore::new(200, 3, "2017-04-22", "CCC")), Some(1));
+    // ties go behind whoever set the score first
+    assert_eq!(table.insert(HighScore::new(100, 4, "2017-04-22", "DDD")), None);
+    assert_eq!(table.insert(HighScore::new(150, 5, "2017-04-22", "EEE")), Some(2));
+
+    let points: Vec<u32> = table.entries.iter().map(|e| e.points).collect();
+    assert_eq!(points, vec![300, 200, 150]);
+    assert!(!table.qualifies(0));
+}
+
+#[test]
+fn round_trips_through_text() {
+    let mut table = HighScoreTable::new(10);
+    table.insert(HighScore::new(1320, 42, "2017-04-23", "one"));
+    table.insert(HighScore::new(870, 7, "2017-04-22", ""));
+
+    let text = table.serialize();
+    assert_eq!(text, "hwd-highscores 1\n1320 42 2017-04-23 ONE\n870 7 2017-04-22 ???\n");
+    assert_eq!(HighScoreTable::parse(&text, 10).unwrap(), table);
+}
+
+#[test]
+fn initials_are_cleaned_up() {
+    assert_eq!(clean_initials("j r r tolkien"), "JRR");
+    assert_eq!(clean_initials(" "), "???");
+}
+
+#[test]
+fn empty_file_is_an_empty_table() {
+    assert_eq!(HighScoreTable::parse("", 10).unwrap(), HighScoreTable::new(10));
+}
+
+#[test]
+fn reports_bad_files() {
+    match HighScoreTable::parse("1320 42 2017-04-23 ONE\n", 10) {
+        Err(HighScoreError::Corrupt { line: 1, .. }) => (),
+        other => panic!("expected a bad header, got {:?}", other),
+    }
+    match HighScoreTable::parse("hwd-highscores 1\n1320 42 2017-04-23 ONE\nlots 1 2017-04-23 TWO\n", 10) {
+        Err(HighScoreError::Corrupt { line: 3, .. }) => (),
+        other => panic!("expected bad points, got {:?}", other),
+    }
+    match HighScoreTable::parse("hwd-highscores 99\n", 10) {
+        Err(HighScoreError::UnsupportedVersion(99)) => (),
+        other => panic!("expected an unsupported version, got {:?}", other),
+    }
+}
+
+#[test]
+fn saves_and_loads_from_the_data_dir() {
+    let dir = scratch_dir("save");
+    let mut scores = HighScores::load(&dir, "abc");
+    assert!(scores.table.entries.is_empty());
+    assert_eq!(scores.record(500, 9), Some(0));
+    assert_eq!(scores.record(0, 10), None);
+
+    let reloaded = HighScores::load(&dir, "xyz");
+    assert_eq!(reloaded.table, scores.table);
+    assert_eq!(reloaded.table.entries[0].initials, "ABC");
+    assert_eq!(reloaded.table.entries[0].seed, 9);
+}
+
+#[test]
+fn corrupt_files_are_moved_aside() {
+    let dir = scratch_dir("corrupt");
+    write(&dir, HIGH_SCORE_FILE, "not a high score table\n");
+
+    let mut scores = HighScores::load(&dir, "abc");
+    assert!(scores.table.entries.is_empty());
+    scores.record(100, 1);
+
+    let mut kept = String::new();
+    File::open(dir.join("highscores.txt.bad")).unwrap().read_to_string(&mut kept).unwrap();
+    assert_eq!(kept, "not a high score table\n");
+    assert_eq!(HighScores::load(&dir, "abc").table.entries.len(), 1);
+}
@@ -0,0 +1,81 @@
+extern crate ggez;
+extern crate ld38;
+
+use ggez::event::{Button, Keycode, MouseButton};
+use ld38::{Action, ActionStates, InputState};
+use ld38::input::{Binding, Controls, InputMap};
+use ld38::recording::Replay;
+
+
+#[test]
+fn default_space_swings_and_confirms() {
+    let map = InputMap::default();
+    assert_eq!(map.actions(&Binding::Key(Keycode::Space)), vec![Action::Swing, Action::Confirm]);
+    assert_eq!(map.actions(&Binding::Key(Keycode::Escape)), vec![Action::Pause]);
+    assert_eq!(map.actions(&Binding::Pad(Button::B)), vec![Action::Back]);
+    assert_eq!(map.actions(&Binding::Key(Keycode::Q)), vec![]);
+}
+
+#[test]
+fn shipped_controls_match_the_defaults() {
+    assert_eq!(InputMap::load("resources/controls.cfg").unwrap(), InputMap::default());
+}
+
+#[test]
+fn parses_bindings() {
+    let map = InputMap::parse("# comment\nswing = key:Z, mouse:right\n\npause = pad:start # trailing\n").unwrap();
+    assert_eq!(map.actions(&Binding::Key(Keycode::Z)), vec![Action::Swing]);
+    assert_eq!(map.actions(&Binding::Mouse(MouseButton::Right)), vec![Action::Swing]);
+    assert_eq!(map.actions(&Binding::Pad(Button::Start)), vec![Action::Pause]);
+    assert_eq!(map.actions(&Binding::Key(Keycode::Space)), vec![]);
+}
+
+#[test]
+fn reports_bad_bindings() {
+    assert!(InputMap::parse("dance = key:Space").unwrap_err().contains("unknown action `dance`"));
+    assert!(InputMap::parse("swing").unwrap_err().contains("line 1"));
+    assert!(InputMap::parse("\nswing = key:NotAKey").unwrap_err().contains("line 2"));
+    assert!(InputMap::parse("swing = joystick:1").is_err());
+}
+
+#[test]
+fn held_actions_only_press_and_release_once() {
+    let mut controls = Controls::new(InputMap::default());
+    assert_eq!(controls.press(Binding::Key(Keycode::Space)), vec![Action::Swing, Action::Confirm]);
+    // key repeat
+    assert_eq!(controls.press(Binding::Key(Keycode::Space)), vec![]);
+    // the mouse is bound to the same actions, which are already down
+    assert_eq!(controls.press(Binding::Mouse(MouseButton::Left)), vec![]);
+    assert_eq!(controls.release(Binding::Key(Keycode::Space)), vec![]);
+    assert_eq!(controls.release(Binding::Mouse(MouseButton::Left)), vec![Action::Swing, Action::Confirm]);
+    // never pressed
+    assert_eq!(controls.release(Binding::Key(Keycode::Escape)), vec![]);
+}
+
+#[test]
+fn just_states_decay_when_nothing_happens() {
+    let mut last = ActionStates::new();
+    last.set(Action::Swing, InputState::JustPressed);
+    last.set(Action::Confirm, InputState::JustReleased);
+    last.set(Action::Pause, InputState::JustPressed);
+
+    let mut current = last.clone();
+    // an event came in for pause since the last tick
+    current.set(Action::Pause, InputState::JustReleased);
+    current.decay(&last);
+
+    assert_eq!(current.get(Action::Swing), InputState::Pressed);
+    assert_eq!(current.get(Action::Confirm), InputState::Released);
+    assert_eq!(current.get(Action::Pause), InputState::JustReleased);
+    assert_eq!(current.get(Action::Back), InputState::Released);
+}
+
+#[test]
+fn replays_carry_every_action() {
+    let replay = Replay::read("seed 3\n16 JustPressed Released JustPressed Released\n16 Pressed\n".as_bytes()).unwrap();
+    assert_eq!(replay.ticks[0].input.get(Action::Swing), InputState::JustPressed);
+    assert_eq!(replay.ticks[0].input.get(Action::Confirm), InputState::JustPressed);
+    // older replays only had the swing button
+    assert_eq!(replay.ticks[1].input.get(Action::Swing), InputState::Pressed);
+    assert_eq!(replay.ticks[1].input.get(Action::Confirm), InputState::Released);
+}
@@ -0,0 +1,72 @@
+extern crate ld38;
+
+use ld38::meter::{MeterModel, SwingGrade, Waveform};
+
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+fn model(waveform: Waveform) -> MeterModel {
+    MeterModel { waveform: waveform, period_ms: 1000., ..MeterModel::default() }
+}
+
+#[test]
+fn waveforms_start_empty_and_stay_in_range() {
+    for &waveform in &[Waveform::Sine, Waveform::Triangle, Waveform::Sawtooth] {
+        let meter = model(waveform);
+        assert!(close(meter.level(0.), 0.), "{:?}", waveform);
+        for step in 0..300 {
+            let level = meter.level(step as f32 * 10.);
+            assert!(level >= 0. && level <= 1., "{:?} hit {}", waveform, level);
+        }
+    }
+}
+
+#[test]
+fn sine_and_triangle_peak_halfway() {
+    for &waveform in &[Waveform::Sine, Waveform::Triangle] {
+        let meter = model(waveform);
+        assert!(close(meter.level(500.), 1.), "{:?}", waveform);
+        assert!(close(meter.level(1000.), 0.), "{:?}", waveform);
+        assert!(close(meter.level(250.), meter.level(750.)), "{:?}", waveform);
+    }
+    assert!(close(model(Waveform::Triangle).level(250.), 0.5));
+    assert!(model(Waveform::Sine).level(100.) < model(Waveform::Triangle).level(100.));
+}
+
+#[test]
+fn sawtooth_drops_back_to_empty() {
+    let meter = model(Waveform::Sawtooth);
+    assert!(close(meter.level(250.), 0.25));
+    assert!(meter.level(990.) > 0.98);
+    assert!(meter.level(1010.) < 0.02);
+}
+
+#[test]
+fn grades_by_distance_from_the_sweet_spot() {
+    let meter = MeterModel { sweet_spot: 0.8, perfect_window: 0.05, good_window: 0.2, ..MeterModel::default() };
+    assert_eq!(meter.grade(0.8), SwingGrade::Perfect);
+    assert_eq!(meter.grade(0.84), SwingGrade::Perfect);
+    assert_eq!(meter.grade(0.76), SwingGrade::Perfect);
+    assert_eq!(meter.grade(0.9), SwingGrade::Good);
+    assert_eq!(meter.grade(0.65), SwingGrade::Good);
+    assert_eq!(meter.grade(0.55), SwingGrade::Weak);
+    assert_eq!(meter.grade(0.), SwingGrade::Weak);
+}
+
+#[test]
+fn better_timing_hits_harder() {
+    assert!(SwingGrade::Perfect.strength() > SwingGrade::Good.strength());
+    assert!(SwingGrade::Good.strength() > SwingGrade::Weak.strength());
+    assert!(SwingGrade::Weak.strength() > 0.);
+}
+
+#[test]
+fn pointer_spans_the_meter() {
+    let meter = MeterModel::default();
+    assert!(close(meter.pointer_offset(0.), -1.));
+    assert!(close(meter.pointer_offset(0.5), 0.));
+    assert!(close(meter.pointer_offset(1.), 1.));
+    assert!(close(meter.pointer_offset(1.5), 1.));
+}
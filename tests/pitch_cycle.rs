@@ -0,0 +1,230 @@
+extern crate ld38;
+extern crate specs;
+
+mod common;
+
+use common::{perfect_swing, safely_perfect, Harness};
+use ld38::{GamePhase, InputState, TickData};
+use ld38::config::{GameConfig, Opponent};
+use ld38::meter::SwingGrade;
+use ld38::pitches::PitchType;
+use ld38::headless::{self, InputSource, ScriptedInput};
+use ld38::systems::{bat_rotation, DrawCommand, BAT_SWING_ARC};
+
+
+#[test]
+fn waits_for_player_until_button_held() {
+    let mut h = Harness::new(1);
+    h.step_for(2000., false);
+    assert_eq!(h.phase(), GamePhase::WaitingForPlayer);
+    assert_eq!(h.timeline.len(), 1);
+}
+
+#[test]
+fn holding_button_starts_windup() {
+    let mut h = Harness::new(1);
+    let reached = h.run_until(GamePhase::Windup, 200., true);
+    assert!(reached.is_some(), "timeline: {:?}", h.timeline);
+}
+
+#[test]
+fn windup_lasts_between_3000_and_5500_ms() {
+    for seed in 0..10 {
+        let mut h = Harness::new(seed);
+        h.run_until(GamePhase::Pitching, 10000., true).expect("never pitched");
+
+        let windup = h.entered(GamePhase::Windup).unwrap();
+        let pitching = h.entered(GamePhase::Pitching).unwrap();
+        let duration = pitching - windup;
+        assert!(duration >= 3000. && duration <= 5500. + h.delta_ms,
+                "seed {} wound up for {}", seed, duration);
+    }
+}
+
+#[test]
+fn unswung_pitch_is_a_miss_held_for_cool_down() {
+    let mut h = Harness::new(7);
+    h.pitch();
+    h.run_until(GamePhase::Miss, 3000., true).expect("ball never left play");
+
+    let miss = h.entered(GamePhase::Miss).unwrap();
+    // let go once the call is made, so we don't swing and don't step right back up to the plate
+    h.step(false);
+    let reset = h.run_until(GamePhase::WaitingForPlayer, 6000., false).expect("never reset");
+
+    let cool_down = reset - miss;
+    assert!(cool_down >= 5000. && cool_down <= 5000. + 2. * h.delta_ms, "cool-down was {}", cool_down);
+    assert!(h.ball().is_none());
+    // PlayerReady only lasts until the pitcher notices, which may be within the same tick
+    let phases: Vec<GamePhase> = h.phases().into_iter().filter(|p| *p != GamePhase::PlayerReady).collect();
+    assert_eq!(phases, vec![
+        GamePhase::WaitingForPlayer,
+        GamePhase::Windup,
+        GamePhase::Pitching,
+        GamePhase::BallInFlight,
+        GamePhase::Miss,
+        GamePhase::WaitingForPlayer,
+    ]);
+}
+
+#[test]
+fn alien_pitches_like_the_machine() {
+    let mut config = GameConfig::default();
+    config.opponent = Opponent::Alien;
+    let mut h = Harness::with_config(3, &config);
+    h.pitch();
+    assert!(h.ball().is_some());
+}
+
+#[test]
+fn ball_spawns_when_pitched() {
+    let mut h = Harness::new(3);
+    assert!(h.ball().is_none());
+    h.pitch();
+
+    let ball = h.ball().expect("no ball in flight");
+    assert!(!ball.struck);
+    assert!(!ball.out_of_bounds);
+}
+
+/// A harness with a fastball in flight.
+fn fastball() -> Harness {
+    for seed in 0..100 {
+        let mut h = Harness::new(seed);
+        h.pitch();
+        if h.pitch_type() == Some(PitchType::Fastball) {
+            return h;
+        }
+    }
+    panic!("no fastballs in 100 seeds");
+}
+
+#[test]
+fn swing_on_release_meets_the_ball() {
+    let mut h = fastball();
+    h.step(false);
+    assert!(h.bat().map_or(false, |bat| bat.active_clip.map_or(false, |c| c.name == "Swing")));
+
+    let mut struck = false;
+    for _ in 0..60 {
+        h.step(false);
+        if h.ball().map_or(false, |ball| ball.struck) {
+            struck = true;
+            break;
+        }
+    }
+    assert!(struck, "ball never met the bat");
+
+    let ball = h.ball().unwrap();
+    // came in straight down the screen, so it should leave straight back up
+    assert!((ball.angle - ld38::systems::BAT_NORMAL).abs() < 0.001, "angle was {}", ball.angle);
+    assert!(ball.velocity > ld38::systems::PITCH_VELOCITY);
+}
+
+#[test]
+fn meter_keeps_moving_while_the_ball_is_on_its_way() {
+    let mut h = Harness::new(3);
+    h.pitch();
+
+    let mut levels = vec![h.meter_level()];
+    loop {
+        h.step(true);
+        if h.phase() != GamePhase::BallInFlight {
+            break;
+        }
+        levels.push(h.meter_level());
+    }
+    assert!(levels.len() > 2, "ball was only in flight for {} ticks", levels.len());
+    assert!(levels.windows(2).all(|pair| pair[0] != pair[1]), "meter stopped: {:?}", levels);
+}
+
+#[test]
+fn swings_in_flight_are_graded_by_the_meter_at_release() {
+    let mut perfect = 0;
+    let mut weak = 0;
+    for seed in 0..20 {
+        let mut h = Harness::new(seed);
+        let released = h.release_when(10000., |phase, level| {
+            *phase == GamePhase::BallInFlight && safely_perfect(level)
+        });
+        if released.is_some() {
+            assert_eq!(h.bat().unwrap().grade, Some(SwingGrade::Perfect), "seed {}", seed);
+            perfect += 1;
+        }
+
+        let mut h = Harness::new(seed);
+        let released = h.release_when(10000., |phase, level| *phase == GamePhase::BallInFlight && level < 0.3);
+        if released.is_some() {
+            assert_eq!(h.bat().unwrap().grade, Some(SwingGrade::Weak), "seed {}", seed);
+            weak += 1;
+        }
+    }
+    assert!(perfect > 0 && weak > 0, "{} perfect and {} weak swings", perfect, weak);
+}
+
+#[test]
+fn letting_go_swings_as_hard_as_the_meter_says() {
+    let mut h = (0..20).filter_map(|seed| perfect_swing(seed, 0)).next().expect("no perfect swings in 20 seeds");
+    let bat = h.bat().unwrap();
+    assert_eq!(bat.power, SwingGrade::Perfect.strength());
+    assert!(bat_rotation(&bat) < 0.);
+
+    // only through the zone once the bat's past its first cell, and not once it's done
+    let mut swinging = vec![bat.swinging];
+    for _ in 0..100 {
+        h.step(false);
+        swinging.push(h.bat().unwrap().swinging);
+    }
+    assert!(!swinging[0]);
+    assert!(swinging.iter().any(|s| *s), "bat never came through the zone");
+    assert!(!swinging[swinging.len() - 1]);
+}
+
+#[test]
+fn bat_turns_about_the_handle_as_it_swings() {
+    let mut h = fastball();
+    assert_eq!(bat_rotation(&h.bat().unwrap()), 0.);
+
+    h.step(false);
+    let mut last = bat_rotation(&h.bat().unwrap());
+    for _ in 0..100 {
+        h.step(false);
+        let rot = bat_rotation(&h.bat().unwrap());
+        assert!(rot <= last, "bat turned back from {} to {}", last, rot);
+        last = rot;
+    }
+    // all the way round, and held there until the next at-bat
+    assert!((last + BAT_SWING_ARC).abs() < 0.001, "finished at {}", last);
+}
+
+#[test]
+fn same_seed_same_game() {
+    let script = || InputSource::Script(ScriptedInput::parse(headless::DEFAULT_DELTA_MS,
+                                                             "500:down,4500:up,15000:down,19000:up").unwrap());
+    let config = GameConfig::default();
+    let a = headless::run(42, 2000, script(), &config).unwrap();
+    let b = headless::run(42, 2000, script(), &config).unwrap();
+    assert_eq!(a.timeline, b.timeline);
+    assert_eq!(a.draw_counts, b.draw_counts);
+
+    let c = headless::run(43, 2000, script(), &config).unwrap();
+    assert!(a.timeline != c.timeline);
+}
+
+#[test]
+fn hud_is_drawn_over_the_field() {
+    let mut h = Harness::new(1);
+    h.ecs.tick(TickData::swing(h.delta_ms, InputState::Released));
+    let commands: Vec<DrawCommand> = h.rx.try_iter().collect();
+
+    let background = commands.iter().position(|command| match *command {
+        DrawCommand::DrawTransformed(ref image, _) => image == "background.png",
+        _ => false,
+    }).expect("no background drawn");
+    let texts: Vec<usize> = commands.iter().enumerate()
+        .filter(|&(_, command)| match *command { DrawCommand::DrawText(..) => true, _ => false })
+        .map(|(i, _)| i)
+        .collect();
+    assert!(!texts.is_empty(), "no HUD drawn");
+    assert!(texts.iter().all(|&i| i > background), "background at {}, text at {:?}", background, texts);
+}
@@ -0,0 +1,107 @@
+extern crate ggez;
+extern crate ld38;
+
+use ggez::graphics::Point;
+use ld38::components::Ball;
+use ld38::pitches::{Difficulty, PitchSpec, PitchType, MAX_BREAK, PITCH_ANGLE};
+use ld38::seeded_rng;
+use ld38::systems::advance_ball;
+
+/// Roughly where the bat is (see the `Bat` in `ECS::new`).
+const PLATE_Y: f32 = 660.;
+const PLATE_X: (f32, f32) = (432., 592.);
+
+fn throw(spec: &PitchSpec) -> Ball {
+    let mut ball = Ball::new(Point::new(512., 560.), PITCH_ANGLE, spec.speed);
+    ball.spin = spec.spin;
+    ball
+}
+
+/// Fly the ball to the plate, returning how long it took.
+fn to_plate(ball: &mut Ball) -> f32 {
+    let mut elapsed = 0.;
+    while ball.pos.y < PLATE_Y {
+        advance_ball(ball, 1000. / 60.);
+        elapsed += 1000. / 60.;
+        assert!(elapsed < 5000., "never reached the plate: {:?}", ball);
+    }
+    elapsed
+}
+
+fn spec(difficulty: Difficulty, kind: PitchType) -> PitchSpec {
+    difficulty.pitch_mix().into_iter().map(|(_, spec)| spec).find(|spec| spec.kind == kind).unwrap()
+}
+
+#[test]
+fn fastballs_come_straight_down() {
+    for &difficulty in &[Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+        let mut ball = throw(&spec(difficulty, PitchType::Fastball));
+        to_plate(&mut ball);
+        assert!((ball.pos.x - 512.).abs() < 0.01, "{:?} fastball drifted to {}", difficulty, ball.pos.x);
+        assert_eq!(ball.angle, PITCH_ANGLE);
+    }
+}
+
+#[test]
+fn curveballs_break_but_stay_hittable() {
+    for &difficulty in &[Difficulty::Normal, Difficulty::Hard] {
+        for &direction in &[1., -1.] {
+            let mut curve = spec(difficulty, PitchType::Curveball);
+            curve.spin *= direction;
+            let mut ball = throw(&curve);
+            to_plate(&mut ball);
+
+            let drift = ball.pos.x - 512.;
+            assert!(drift * direction < -1., "{:?} curveball only moved {}", difficulty, drift);
+            assert!(ball.pos.x > PLATE_X.0 && ball.pos.x < PLATE_X.1, "{:?} curveball missed the bat", difficulty);
+            assert!((ball.angle - PITCH_ANGLE).abs() <= MAX_BREAK + 1e-5);
+        }
+    }
+}
+
+#[test]
+fn breaking_stops_once_hit() {
+    let mut ball = throw(&spec(Difficulty::Hard, PitchType::Curveball));
+    advance_ball(&mut ball, 100.);
+    ball.struck = true;
+    let angle = ball.angle;
+    advance_ball(&mut ball, 100.);
+    assert_eq!(ball.angle, angle);
+}
+
+#[test]
+fn changeups_arrive_late() {
+    for &difficulty in &[Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+        let fast = spec(difficulty, PitchType::Fastball);
+        let change = spec(difficulty, PitchType::Changeup);
+        let fast_time = fast.release_delay_ms + to_plate(&mut throw(&fast));
+        let change_time = change.release_delay_ms + to_plate(&mut throw(&change));
+        assert!(change_time > fast_time + 100., "{:?}: {} vs {}", difficulty, change_time, fast_time);
+    }
+}
+
+#[test]
+fn harder_is_faster_with_more_breaking_balls() {
+    let share = |difficulty: Difficulty, kind: PitchType| {
+        let mix = difficulty.pitch_mix();
+        let total: u32 = mix.iter().map(|&(w, _)| w).sum();
+        mix.iter().filter(|&&(_, ref s)| s.kind == kind).map(|&(w, _)| w).sum::<u32>() as f32 / total as f32
+    };
+    assert_eq!(share(Difficulty::Easy, PitchType::Curveball), 0.);
+    assert!(share(Difficulty::Hard, PitchType::Curveball) > share(Difficulty::Normal, PitchType::Curveball));
+    assert!(spec(Difficulty::Hard, PitchType::Fastball).speed > spec(Difficulty::Normal, PitchType::Fastball).speed);
+    assert!(spec(Difficulty::Normal, PitchType::Fastball).speed > spec(Difficulty::Easy, PitchType::Fastball).speed);
+}
+
+#[test]
+fn every_pitch_in_the_mix_gets_thrown() {
+    let mut rng = seeded_rng(11);
+    let mut seen = vec![];
+    for _ in 0..200 {
+        let pitch = Difficulty::Normal.choose_pitch(&mut rng);
+        if !seen.contains(&pitch.kind) {
+            seen.push(pitch.kind);
+        }
+    }
+    assert_eq!(seen.len(), 3);
+}
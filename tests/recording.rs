@@ -0,0 +1,83 @@
+extern crate ld38;
+
+use std::env;
+
+use ld38::config::{GameConfig, Opponent};
+use ld38::meter::Waveform;
+use ld38::pitches::Difficulty;
+use ld38::recording::{Replay, ReplayError, TickRecorder};
+
+
+const TICKS: &'static str = "16.5 Released\n16.5 JustPressed\n17 Pressed\n15.25 JustReleased\n";
+
+#[test]
+fn recordings_play_back_what_was_recorded() {
+    let original = Replay::read(format!("seed 1234\n{}", TICKS).as_bytes()).unwrap();
+
+    let mut config = GameConfig::default();
+    config.difficulty = Difficulty::Hard;
+    config.opponent = Opponent::Alien;
+    config.timing.windup_min_ms = 2000.;
+    config.meter.waveform = Waveform::Sawtooth;
+    config.meter.period_ms = 900.;
+    config.layout.bat = (500., 680.);
+
+    let path = env::temp_dir().join("hwd-round-trip.replay");
+    {
+        let mut recorder = TickRecorder::create(&path, original.seed, &config).unwrap();
+        for tick in &original.ticks {
+            recorder.record(tick).unwrap();
+        }
+    }
+
+    let replay = Replay::load(&path).unwrap();
+    assert_eq!(replay.seed, 1234);
+    assert_eq!(replay.config, Some(config));
+    assert_eq!(replay.ticks.len(), 4);
+    assert_eq!(replay.ticks, original.ticks);
+}
+
+#[test]
+fn playback_uses_the_recorded_settings_in_the_current_window() {
+    let settings = "config difficulty = \"easy\"\nconfig [meter]\nconfig period_ms = 700.0\n";
+    let text = format!("seed 1\n{}{}", settings, TICKS);
+    let replay = Replay::read(text.as_bytes()).unwrap();
+
+    let mut current = GameConfig::default();
+    current.difficulty = Difficulty::Hard;
+    current.window.width = 1280;
+
+    let config = replay.playback_config(&current);
+    assert_eq!(config.difficulty, Difficulty::Easy);
+    assert_eq!(config.meter.period_ms, 700.);
+    assert_eq!(config.window.width, 1280);
+}
+
+#[test]
+fn older_replays_keep_the_current_settings() {
+    let replay = Replay::read(format!("seed 1\n{}", TICKS).as_bytes()).unwrap();
+    assert_eq!(replay.config, None);
+
+    let mut current = GameConfig::default();
+    current.opponent = Opponent::Alien;
+    assert_eq!(replay.playback_config(&current), current);
+}
+
+#[test]
+fn bad_settings_are_reported() {
+    let text = format!("seed 1\nconfig difficulty = \"impossible\"\n{}", TICKS);
+    match Replay::read(text.as_bytes()) {
+        Err(ReplayError::Settings(_)) => (),
+        other => panic!("expected bad settings, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn errors_only_give_a_line_when_theres_one_to_give() {
+    match Replay::read(TICKS.as_bytes()) {
+        Err(ReplayError::Line { line_no: 1, .. }) => (),
+        other => panic!("expected the first line to be blamed, got {:?}", other.map(|_| ())),
+    }
+    let e = Replay::read("".as_bytes()).unwrap_err();
+    assert_eq!(e.to_string(), "replay has no `seed` line");
+}
@@ -0,0 +1,40 @@
+extern crate ggez;
+extern crate ld38;
+
+use ggez::graphics::{Point, Rect};
+use ld38::render::{draw_spec, Transform};
+
+
+#[test]
+fn whole_image_when_no_cell() {
+    let spec = draw_spec(&Transform::at(10., 20.), 64., 32., None);
+    assert_eq!(spec.src, Rect::new(0., 0., 1., 1.));
+    assert_eq!(spec.dest, Point::new(10., 20.));
+}
+
+#[test]
+fn cell_is_normalized_to_the_atlas() {
+    let spec = draw_spec(&Transform::at(0., 0.), 512., 256., Some(Rect::new(128., 64., 64., 64.)));
+    assert_eq!(spec.src, Rect::new(0.25, 0.25, 0.125, 0.25));
+}
+
+#[test]
+fn centered_origin_has_no_offset() {
+    let spec = draw_spec(&Transform::at(0., 0.), 64., 64., None);
+    assert_eq!(spec.offset, Point::new(0., 0.));
+}
+
+#[test]
+fn origin_becomes_offset_from_center() {
+    let xform = Transform { origin: Point::new(0.25, 0.75), ..Transform::at(0., 0.) };
+    let spec = draw_spec(&xform, 64., 64., None);
+    assert_eq!(spec.offset, Point::new(-0.25, 0.25));
+}
+
+#[test]
+fn scale_and_rotation_pass_through() {
+    let xform = Transform { scale: Point::new(2., 3.), rot: 1.5, ..Transform::at(0., 0.) };
+    let spec = draw_spec(&xform, 64., 64., None);
+    assert_eq!(spec.scale, Point::new(2., 3.));
+    assert_eq!(spec.rotation, 1.5);
+}
@@ -0,0 +1,90 @@
+extern crate ld38;
+
+use ld38::GamePhase;
+use ld38::GamePhase::{Foul, Hit, HomeRun, Miss};
+use ld38::rules::{MatchRules, RulesEvent};
+
+
+/// Feed the calls in, checking the event from each one along with the count afterwards.
+fn check(mut rules: MatchRules, table: &[(GamePhase, RulesEvent, u32, u32, u32)]) -> MatchRules {
+    for (i, &(ref call, ref event, strikes, outs, inning)) in table.iter().enumerate() {
+        let got = rules.record(call);
+        assert_eq!((&got, rules.strikes, rules.outs, rules.inning), (event, strikes, outs, inning),
+                   "row {}: {:?}", i, call);
+    }
+    rules
+}
+
+#[test]
+fn derby_counts_everything_but_home_runs_as_outs() {
+    let rules = check(MatchRules::derby(3), &[
+        (HomeRun, RulesEvent::AtBatOver, 0, 0, 1),
+        (Hit,     RulesEvent::Out,       0, 1, 1),
+        (Foul,    RulesEvent::Out,       0, 2, 1),
+        (HomeRun, RulesEvent::AtBatOver, 0, 2, 1),
+        (Miss,    RulesEvent::GameOver,  0, 3, 1),
+        (HomeRun, RulesEvent::GameOver,  0, 3, 1),
+    ]);
+    assert!(rules.game_over);
+    assert_eq!(rules.home_runs, 2);
+    assert_eq!(rules.runs, 2);
+}
+
+#[test]
+fn three_strikes_is_an_out() {
+    check(MatchRules::innings(9), &[
+        (Miss, RulesEvent::Pitch,     1, 0, 1),
+        (Miss, RulesEvent::Pitch,     2, 0, 1),
+        (Miss, RulesEvent::Strikeout, 0, 1, 1),
+    ]);
+}
+
+#[test]
+fn fouls_stop_at_two_strikes() {
+    check(MatchRules::innings(9), &[
+        (Foul, RulesEvent::Pitch,     1, 0, 1),
+        (Foul, RulesEvent::Pitch,     2, 0, 1),
+        (Foul, RulesEvent::Pitch,     2, 0, 1),
+        (Foul, RulesEvent::Pitch,     2, 0, 1),
+        (Miss, RulesEvent::Strikeout, 0, 1, 1),
+    ]);
+}
+
+#[test]
+fn balls_in_play_reset_the_count() {
+    let rules = check(MatchRules::innings(9), &[
+        (Miss,    RulesEvent::Pitch,     1, 0, 1),
+        (Hit,     RulesEvent::AtBatOver, 0, 0, 1),
+        (Foul,    RulesEvent::Pitch,     1, 0, 1),
+        (HomeRun, RulesEvent::AtBatOver, 0, 0, 1),
+    ]);
+    assert_eq!(rules.runs, 1);
+}
+
+#[test]
+fn three_outs_ends_the_inning_and_last_inning_ends_the_game() {
+    let k = |outs, inning, last| vec![
+        (Miss, RulesEvent::Pitch, 1, outs - 1, inning),
+        (Miss, RulesEvent::Pitch, 2, outs - 1, inning),
+        (Miss, last, 0, outs % 3, if outs == 3 && inning < 2 { inning + 1 } else { inning }),
+    ];
+
+    let mut table = vec![];
+    table.extend(k(1, 1, RulesEvent::Strikeout));
+    table.extend(k(2, 1, RulesEvent::Strikeout));
+    table.extend(k(3, 1, RulesEvent::InningOver));
+    table.extend(k(1, 2, RulesEvent::Strikeout));
+    table.extend(k(2, 2, RulesEvent::Strikeout));
+    table.extend(k(3, 2, RulesEvent::GameOver));
+
+    let rules = check(MatchRules::innings(2), &table);
+    assert!(rules.game_over);
+    assert_eq!(rules.inning, 2);
+}
+
+#[test]
+fn other_phases_are_ignored() {
+    let mut rules = MatchRules::innings(9);
+    assert_eq!(rules.record(&GamePhase::BallInFlight), RulesEvent::Pitch);
+    assert_eq!(rules, MatchRules::innings(9));
+}
@@ -0,0 +1,137 @@
+extern crate ld38;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use ld38::Action;
+use ld38::config::GameConfig;
+use ld38::headless::DEFAULT_DELTA_MS;
+use ld38::rules::MatchRules;
+use ld38::scenes::{GameplayScene, SceneEvent, SceneStack, TitleScene};
+use ld38::sheets::{SheetRegistry, DEFAULT_SHEETS};
+use ld38::systems::DrawCommand;
+
+
+fn sheets() -> Rc<RefCell<SheetRegistry>> {
+    Rc::new(RefCell::new(SheetRegistry::load("resources", DEFAULT_SHEETS).unwrap()))
+}
+
+fn config() -> Rc<GameConfig> {
+    Rc::new(GameConfig::default())
+}
+
+fn title_stack() -> SceneStack {
+    SceneStack::new(Box::new(TitleScene::new(sheets(), config(), 1)))
+}
+
+/// What the default bindings send for Space.
+fn button_down(stack: &mut SceneStack) {
+    stack.input(SceneEvent::Pressed(Action::Swing));
+    stack.input(SceneEvent::Pressed(Action::Confirm));
+}
+
+fn button_up(stack: &mut SceneStack) {
+    stack.input(SceneEvent::Released(Action::Swing));
+    stack.input(SceneEvent::Released(Action::Confirm));
+}
+
+fn click(stack: &mut SceneStack) {
+    button_down(stack);
+    button_up(stack);
+}
+
+fn pause(stack: &mut SceneStack) {
+    stack.input(SceneEvent::Pressed(Action::Pause));
+    stack.input(SceneEvent::Released(Action::Pause));
+}
+
+#[test]
+fn title_starts_the_game_on_release() {
+    let mut stack = title_stack();
+    button_down(&mut stack);
+    assert_eq!(stack.names(), vec!["title"]);
+    button_up(&mut stack);
+    assert_eq!(stack.names(), vec!["gameplay"]);
+}
+
+#[test]
+fn pause_overlays_the_game() {
+    let mut stack = title_stack();
+    click(&mut stack);
+    stack.update(DEFAULT_DELTA_MS);
+    let frame = stack.draw();
+    assert!(!frame.is_empty());
+
+    pause(&mut stack);
+    assert_eq!(stack.names(), vec!["gameplay", "pause"]);
+    // the game stays on screen, frozen, under the pause menu
+    stack.update(DEFAULT_DELTA_MS);
+    assert_eq!(stack.draw().len(), frame.len());
+
+    // only pause gets us out of pause
+    click(&mut stack);
+    assert_eq!(stack.names(), vec!["gameplay", "pause"]);
+    pause(&mut stack);
+    assert_eq!(stack.names(), vec!["gameplay"]);
+}
+
+#[test]
+fn only_the_last_tick_is_drawn() {
+    let mut stack = title_stack();
+    click(&mut stack);
+    stack.update(DEFAULT_DELTA_MS);
+    let one_tick = stack.draw().len();
+
+    // a slow frame catching up
+    for _ in 0..3 {
+        stack.update(DEFAULT_DELTA_MS);
+    }
+    assert_eq!(stack.draw().len(), one_tick);
+}
+
+#[test]
+fn title_text_is_centred_in_any_window() {
+    let mut config = GameConfig::default();
+    config.window.width = 1600;
+    config.window.height = 900;
+    let mut stack = SceneStack::new(Box::new(TitleScene::new(sheets(), Rc::new(config), 1)));
+    let texts: Vec<f32> = stack.draw().into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText(_, xform) => Some(xform.pos.x),
+            _ => None,
+        })
+        .collect();
+    assert!(!texts.is_empty());
+    assert!(texts.iter().all(|&x| x == 800.), "{:?}", texts);
+}
+
+#[test]
+fn pause_on_title_quits() {
+    let mut stack = title_stack();
+    pause(&mut stack);
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn round_ends_in_results_then_title() {
+    let game = GameplayScene::new(sheets(), config(), 5, MatchRules::derby(1)).unwrap();
+    let mut stack = SceneStack::new(Box::new(game));
+
+    // hold the button through the pitch and never swing
+    button_down(&mut stack);
+    let mut elapsed = 0.;
+    let mut down = true;
+    while stack.top_name() == Some("gameplay") && elapsed < 20000. {
+        stack.update(DEFAULT_DELTA_MS);
+        elapsed += DEFAULT_DELTA_MS;
+        if down && elapsed > 8000. {
+            // after the call (6900ms at the latest, for a changeup) but before the reset (8900ms at the earliest)
+            button_up(&mut stack);
+            down = false;
+        }
+    }
+    assert_eq!(stack.names(), vec!["results"]);
+
+    click(&mut stack);
+    assert_eq!(stack.names(), vec!["title"]);
+}
@@ -0,0 +1,70 @@
+extern crate ggez;
+extern crate ld38;
+
+use ggez::graphics::Point;
+use ld38::GamePhase;
+use ld38::components::Ball;
+use ld38::score::{Score, HOME_RUN_BONUS, MAX_MULTIPLIER};
+use ld38::systems::{projected_distance, BAT_NORMAL, FAIR_ARC, FEET_PER_VELOCITY};
+
+
+#[test]
+fn hits_score_by_distance() {
+    let mut score = Score::default();
+    assert_eq!(score.award(&GamePhase::Hit, 250.), 25);
+    assert_eq!(score.points, 25);
+    assert_eq!(score.streak, 1);
+}
+
+#[test]
+fn home_runs_get_a_bonus() {
+    let mut score = Score::default();
+    assert_eq!(score.award(&GamePhase::HomeRun, 400.), 40 + HOME_RUN_BONUS);
+}
+
+#[test]
+fn streaks_raise_the_multiplier() {
+    let mut score = Score::default();
+    score.award(&GamePhase::Hit, 100.);
+    score.award(&GamePhase::Hit, 100.);
+    assert_eq!(score.multiplier(), 2);
+    assert_eq!(score.award(&GamePhase::Hit, 100.), 20);
+    assert_eq!(score.points, 40);
+
+    for _ in 0..10 {
+        score.award(&GamePhase::Hit, 100.);
+    }
+    assert_eq!(score.multiplier(), MAX_MULTIPLIER);
+}
+
+#[test]
+fn fouls_and_misses_reset_the_streak() {
+    let mut score = Score::default();
+    for _ in 0..3 {
+        score.award(&GamePhase::Hit, 100.);
+    }
+    let points = score.points;
+
+    assert_eq!(score.award(&GamePhase::Foul, 100.), 0);
+    assert_eq!(score.streak, 0);
+    assert_eq!(score.best_streak, 3);
+    assert_eq!(score.points, points);
+
+    score.award(&GamePhase::Hit, 100.);
+    assert_eq!(score.award(&GamePhase::Miss, 0.), 0);
+    assert_eq!(score.streak, 0);
+    assert_eq!(score.multiplier(), 1);
+}
+
+#[test]
+fn distance_is_furthest_straight_up_the_middle() {
+    let mut ball = Ball::new(Point::new(512., 0.), BAT_NORMAL, 1.);
+    assert_eq!(projected_distance(&ball), 0.);
+
+    ball.struck = true;
+    assert_eq!(projected_distance(&ball), FEET_PER_VELOCITY);
+
+    ball.angle = BAT_NORMAL + FAIR_ARC / 2.;
+    let angled = projected_distance(&ball);
+    assert!(angled > 0. && angled < FEET_PER_VELOCITY);
+}
@@ -0,0 +1,212 @@
+extern crate ggez;
+extern crate ld38;
+extern crate omn_labs;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+
+use ggez::graphics::Rect;
+use omn_labs::sprites::PlayMode;
+use ld38::config::Opponent;
+use ld38::error::AssetError;
+use ld38::sheets::{SheetError, SheetRegistry, DEFAULT_SHEETS};
+
+
+#[test]
+fn exports_pair_with_their_images() {
+    let mut sheets = SheetRegistry::new();
+    sheets.load_export("resources", "bat").unwrap();
+    assert_eq!(sheets.get("bat").unwrap().image, "bat.png");
+    assert_eq!(sheets.images(), vec!["bat.png"]);
+    assert!(sheets.pixels().is_empty());
+}
+
+#[test]
+fn ase_files_bring_their_own_pixels() {
+    let sheets = SheetRegistry::load("resources", DEFAULT_SHEETS).unwrap();
+    assert!(sheets.images().is_empty());
+    let names: Vec<&str> = sheets.pixels().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec![
+        "alien.ase", "bar.ase", "bat.ase", "cap.ase", "pitcher.ase", "pitching-machine.ase", "pointer.ase", "ship.ase",
+    ]);
+
+    let (_, bat) = sheets.pixels().into_iter().find(|&(name, _)| name == "bat.ase").unwrap();
+    // three frames, two to a row
+    assert_eq!((bat.width, bat.height), (2 * 128, 2 * 128));
+}
+
+#[test]
+fn ase_and_export_agree() {
+    let from_ase = SheetRegistry::load("resources", DEFAULT_SHEETS).unwrap();
+    for id in &["bat", "pitching-machine", "pitcher", "bar", "pointer"] {
+        let mut from_export = SheetRegistry::new();
+        from_export.load_export("resources", id).unwrap();
+
+        let (ase, export) = (&from_ase.get(id).unwrap().data, &from_export.get(id).unwrap().data);
+        assert_eq!(ase.cells.len(), export.cells.len(), "{}", id);
+        for idx in 0..ase.cells.len() {
+            let (a, b) = (from_ase.cell(id, idx).unwrap(), from_export.cell(id, idx).unwrap());
+            assert_eq!((a.w, a.h), (b.w, b.h), "{} cell {}", id, idx);
+        }
+    }
+
+    for name in &["Ready", "Winding", "Pitching", "Not Ready"] {
+        let mut export = SheetRegistry::new();
+        export.load_export("resources", "pitching-machine").unwrap();
+        let a = from_ase.clips("pitching-machine").unwrap().create(name, PlayMode::Loop).unwrap();
+        let b = export.clips("pitching-machine").unwrap().create(name, PlayMode::Loop).unwrap();
+        assert_eq!(a.duration, b.duration, "{}", name);
+    }
+}
+
+#[test]
+fn every_opponent_can_pitch() {
+    let sheets = SheetRegistry::load("resources", DEFAULT_SHEETS).unwrap();
+    for opponent in &[Opponent::Machine, Opponent::Alien] {
+        let clips = sheets.clips(opponent.sheet()).unwrap();
+        for name in &["Ready", "Winding", "Pitching", "Not Ready"] {
+            assert!(clips.create(name, PlayMode::Loop).is_some(), "{:?} has no `{}` clip", opponent, name);
+        }
+    }
+}
+
+#[test]
+fn exported_sprites_have_their_frames() {
+    let sheets = SheetRegistry::load("resources", DEFAULT_SHEETS).unwrap();
+    assert_eq!(sheets.cell("alien", 0).unwrap(), Rect::new(0., 0., 128., 339.));
+    assert_eq!(sheets.cell("cap", 0).unwrap(), Rect::new(0., 0., 64., 64.));
+    assert_eq!(sheets.cell("ship", 1).unwrap(), Rect::new(128., 0., 128., 128.));
+}
+
+/// A folder with just the export of the bat in it (and maybe an `.ase`).
+fn export_only(name: &str, ase: Option<&[u8]>) -> String {
+    let dir = env::temp_dir().join(format!("hwd-sheets-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy("resources/bat.json", dir.join("bat.json")).unwrap();
+    fs::copy("resources/bat.png", dir.join("bat.png")).unwrap();
+    if let Some(bytes) = ase {
+        File::create(dir.join("bat.ase")).unwrap().write_all(bytes).unwrap();
+    }
+    dir.to_string_lossy().into_owned()
+}
+
+#[test]
+fn falls_back_to_the_export_without_an_ase() {
+    let sheets = SheetRegistry::load(&export_only("missing", None), &["bat"]).unwrap();
+    assert_eq!(sheets.get("bat").unwrap().image, "bat.png");
+    assert_eq!(sheets.images(), vec!["bat.png"]);
+}
+
+#[test]
+fn falls_back_to_the_export_for_a_broken_ase() {
+    let sheets = SheetRegistry::load(&export_only("broken", Some(b"not really an ase")), &["bat"]).unwrap();
+    assert_eq!(sheets.get("bat").unwrap().image, "bat.png");
+}
+
+#[test]
+fn cells_come_from_the_sheet_json() {
+    let mut sheets = SheetRegistry::new();
+    sheets.load_export("resources", "bat").unwrap();
+    assert_eq!(sheets.cell("bat", 2).unwrap(), Rect::new(128., 0., 128., 128.));
+}
+
+#[test]
+fn cells_from_an_ase_are_in_a_grid() {
+    let sheets = SheetRegistry::load("resources", DEFAULT_SHEETS).unwrap();
+    assert_eq!(sheets.cell("bat", 1).unwrap(), Rect::new(128., 0., 128., 128.));
+    assert_eq!(sheets.cell("bat", 2).unwrap(), Rect::new(0., 128., 128., 128.));
+}
+
+#[test]
+fn unknown_sheet() {
+    let sheets = SheetRegistry::load("resources", DEFAULT_SHEETS).unwrap();
+    assert_eq!(sheets.cell("unicorn", 0).unwrap_err(), SheetError::UnknownSheet("unicorn".to_string()));
+}
+
+#[test]
+fn unknown_cell() {
+    let sheets = SheetRegistry::load("resources", DEFAULT_SHEETS).unwrap();
+    assert_eq!(sheets.cell("pointer", 2).unwrap_err(),
+               SheetError::UnknownCell { sheet: "pointer".to_string(), idx: 2, count: 2 });
+}
+
+fn contents(path: &str) -> Vec<u8> {
+    let mut bytes = vec![];
+    File::open(path).unwrap().read_to_end(&mut bytes).unwrap();
+    bytes
+}
+
+fn rewrite(dir: &str, file: &str, contents: &[u8]) {
+    File::create(format!("{}/{}", dir, file)).unwrap().write_all(contents).unwrap();
+}
+
+#[test]
+fn reloading_reaches_clips_already_handed_out() {
+    let dir = export_only("reload", None);
+    let mut sheets = SheetRegistry::load(&dir, &["bat"]).unwrap();
+    let held = sheets.live_clips("bat").unwrap();
+    let before = held.create("Swing", PlayMode::OneShot).unwrap().duration;
+
+    let json = String::from_utf8(contents("resources/bat.json")).unwrap()
+        .replace("\"duration\": 500", "\"duration\": 800");
+    rewrite(&dir, "bat.json", json.as_bytes());
+    sheets.reload(&dir, "bat.json").unwrap();
+
+    assert_eq!(held.create("Swing", PlayMode::OneShot).unwrap().duration, before + 300.);
+    assert_eq!(sheets.clips("bat").unwrap().create("Swing", PlayMode::OneShot).unwrap().duration, before + 300.);
+}
+
+#[test]
+fn broken_reloads_keep_the_old_sheet() {
+    let dir = export_only("broken-reload", None);
+    let mut sheets = SheetRegistry::load(&dir, &["bat"]).unwrap();
+    let held = sheets.live_clips("bat").unwrap();
+    let before = held.create("Swing", PlayMode::OneShot).unwrap().duration;
+
+    rewrite(&dir, "bat.json", b"{ \"frames\": [");
+    match sheets.reload(&dir, "bat.json") {
+        Err(AssetError::MalformedSheet { ref path, .. }) if path.ends_with("bat.json") => (),
+        other => panic!("expected a malformed sheet, got {:?}", other),
+    }
+    assert_eq!(held.create("Swing", PlayMode::OneShot).unwrap().duration, before);
+    assert_eq!(sheets.cell("bat", 2).unwrap(), Rect::new(128., 0., 128., 128.));
+}
+
+#[test]
+fn reloads_an_ase_from_where_it_came_from() {
+    let dir = export_only("reload-ase", Some(&contents("resources/bat.ase")[..]));
+    let mut sheets = SheetRegistry::load(&dir, &["bat"]).unwrap();
+    sheets.reload(&dir, "bat.ase").unwrap();
+    assert_eq!(sheets.get("bat").unwrap().image, "bat.ase");
+
+    rewrite(&dir, "bat.ase", b"half an ase");
+    assert!(sheets.reload(&dir, "bat.ase").is_err());
+    assert_eq!(sheets.pixels().len(), 1);
+    assert_eq!(sheets.cell("bat", 2).unwrap(), Rect::new(0., 128., 128., 128.));
+}
+
+#[test]
+fn editing_the_export_of_an_ase_sheet_brings_it_in() {
+    let dir = export_only("reload-export", Some(&contents("resources/bat.ase")[..]));
+    let mut sheets = SheetRegistry::load(&dir, &["bat"]).unwrap();
+    assert_eq!(sheets.get("bat").unwrap().image, "bat.ase");
+
+    sheets.reload(&dir, "bat.png").unwrap();
+    assert_eq!(sheets.get("bat").unwrap().image, "bat.png");
+    assert!(sheets.pixels().is_empty());
+    assert_eq!(sheets.cell("bat", 2).unwrap(), Rect::new(128., 0., 128., 128.));
+}
+
+#[test]
+fn sheets_are_found_by_any_of_their_files() {
+    let sheets = SheetRegistry::load(&export_only("sheet-for", Some(&contents("resources/bat.ase")[..])), &["bat"])
+        .unwrap();
+    for file in &["bat.ase", "bat.json", "bat.png"] {
+        assert_eq!(sheets.sheet_for(file), Some("bat"), "{}", file);
+    }
+    assert_eq!(sheets.sheet_for("bat.toml"), None);
+    assert_eq!(sheets.sheet_for("pointer.json"), None);
+    assert_eq!(sheets.sheet_for("background.png"), None);
+}
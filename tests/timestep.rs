@@ -0,0 +1,57 @@
+extern crate ld38;
+
+use std::time::Duration;
+
+use ld38::timestep::{duration_ms, FixedTimestep};
+
+
+#[test]
+fn whole_seconds_count() {
+    assert_eq!(duration_ms(Duration::new(2, 500_000_000)), 2500.);
+}
+
+#[test]
+fn ticks_once_per_step() {
+    let mut timestep = FixedTimestep::new(100);
+    assert_eq!(timestep.step_ms, 10.);
+    assert_eq!(timestep.advance(25.), 2);
+    // the leftover half step carries over
+    assert_eq!(timestep.advance(5.), 1);
+    // ...and is used up
+    assert_eq!(timestep.advance(9.), 0);
+    assert_eq!(timestep.advance(1.), 1);
+}
+
+#[test]
+fn alpha_is_how_far_into_the_next_step() {
+    let mut timestep = FixedTimestep::new(100);
+    assert_eq!(timestep.alpha(), 0.);
+    timestep.advance(25.);
+    assert_eq!(timestep.alpha(), 0.5);
+    timestep.advance(2.5);
+    assert_eq!(timestep.alpha(), 0.75);
+    timestep.advance(2.5);
+    assert_eq!(timestep.alpha(), 0.);
+}
+
+#[test]
+fn same_ticks_however_the_frames_fall() {
+    let mut fast = FixedTimestep::new(60);
+    let mut slow = FixedTimestep::new(60);
+    let fast_ticks: u32 = (0..120).map(|_| fast.advance(1000. / 120.)).sum();
+    let slow_ticks: u32 = (0..30).map(|_| slow.advance(1000. / 30.)).sum();
+    assert!((fast_ticks as i32 - 60).abs() <= 1);
+    assert!((slow_ticks as i32 - 60).abs() <= 1);
+}
+
+#[test]
+fn long_frames_drop_time_instead_of_spiraling() {
+    let mut timestep = FixedTimestep::new(100);
+    timestep.max_steps = 5;
+    assert_eq!(timestep.advance(1005.), 5);
+    // only the half step we were into is kept
+    assert_eq!(timestep.advance(4.), 0);
+    assert_eq!(timestep.advance(1.), 1);
+    // caught up, back to normal
+    assert_eq!(timestep.advance(10.), 1);
+}
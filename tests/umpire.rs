@@ -0,0 +1,54 @@
+extern crate ggez;
+extern crate ld38;
+
+use std::f32::consts::PI;
+
+use ggez::graphics::Point;
+use ld38::GamePhase;
+use ld38::components::Ball;
+use ld38::systems::{judge_pitch, BAT_NORMAL, FAIR_ARC, HOME_RUN_VELOCITY};
+
+
+fn ball(angle: f32, velocity: f32, struck: bool, out_of_bounds: bool) -> Ball {
+    let mut ball = Ball::new(Point::new(512., 0.), angle, velocity);
+    ball.struck = struck;
+    ball.out_of_bounds = out_of_bounds;
+    ball
+}
+
+#[test]
+fn no_call_while_in_play() {
+    assert_eq!(judge_pitch(&ball(BAT_NORMAL, 1., true, false)), None);
+    assert_eq!(judge_pitch(&ball(PI / 2., 0.4, false, false)), None);
+}
+
+#[test]
+fn unstruck_ball_is_a_miss() {
+    assert_eq!(judge_pitch(&ball(PI / 2., 0.4, false, true)), Some(GamePhase::Miss));
+}
+
+#[test]
+fn fair_arc_boundary() {
+    let inside = FAIR_ARC - 0.01;
+    let outside = FAIR_ARC + 0.01;
+    let slow = HOME_RUN_VELOCITY / 2.;
+
+    assert_eq!(judge_pitch(&ball(BAT_NORMAL + inside, slow, true, true)), Some(GamePhase::Hit));
+    assert_eq!(judge_pitch(&ball(BAT_NORMAL - inside, slow, true, true)), Some(GamePhase::Hit));
+    assert_eq!(judge_pitch(&ball(BAT_NORMAL + outside, slow, true, true)), Some(GamePhase::Foul));
+    assert_eq!(judge_pitch(&ball(BAT_NORMAL - outside, slow, true, true)), Some(GamePhase::Foul));
+}
+
+#[test]
+fn fouls_regardless_of_speed() {
+    let wide = BAT_NORMAL + FAIR_ARC + 0.5;
+    assert_eq!(judge_pitch(&ball(wide, HOME_RUN_VELOCITY * 2., true, true)), Some(GamePhase::Foul));
+    // straight back at the pitching machine
+    assert_eq!(judge_pitch(&ball(PI / 2., HOME_RUN_VELOCITY * 2., true, true)), Some(GamePhase::Foul));
+}
+
+#[test]
+fn home_run_velocity_boundary() {
+    assert_eq!(judge_pitch(&ball(BAT_NORMAL, HOME_RUN_VELOCITY, true, true)), Some(GamePhase::HomeRun));
+    assert_eq!(judge_pitch(&ball(BAT_NORMAL, HOME_RUN_VELOCITY - 0.01, true, true)), Some(GamePhase::Hit));
+}
@@ -0,0 +1,55 @@
+extern crate ld38;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use ld38::watch::{DirWatcher, WATCH_INTERVAL_MS};
+
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("hwd-watch-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(dir: &PathBuf, name: &str, contents: &str) {
+    File::create(dir.join(name)).unwrap().write_all(contents.as_bytes()).unwrap();
+}
+
+#[test]
+fn files_already_there_are_not_changes() {
+    let dir = scratch_dir("quiet");
+    write(&dir, "bat.json", "{}");
+    let mut watcher = DirWatcher::new(&dir);
+    assert!(watcher.scan().is_empty());
+}
+
+#[test]
+fn notices_edits_and_new_files() {
+    let dir = scratch_dir("edits");
+    write(&dir, "bat.json", "{}");
+    write(&dir, "bar.json", "{}");
+    let mut watcher = DirWatcher::new(&dir);
+
+    write(&dir, "bat.json", "{ \"frames\": [] }");
+    write(&dir, "pointer.png", "not really a png");
+    fs::create_dir(dir.join("subfolder")).unwrap();
+    assert_eq!(watcher.scan(), vec!["bat.json", "pointer.png"]);
+
+    // each change only comes up once
+    assert!(watcher.scan().is_empty());
+}
+
+#[test]
+fn polling_waits_between_scans() {
+    let dir = scratch_dir("poll");
+    let mut watcher = DirWatcher::new(&dir);
+
+    write(&dir, "bat.json", "{}");
+    assert!(watcher.poll(WATCH_INTERVAL_MS / 2.).is_empty());
+    assert_eq!(watcher.poll(WATCH_INTERVAL_MS / 2.), vec!["bat.json"]);
+    assert!(watcher.poll(WATCH_INTERVAL_MS).is_empty());
+}